@@ -0,0 +1,710 @@
+use std::collections::HashMap;
+use std::fmt;
+
+const ROM_START: u16 = 0x200;
+// Guards against macros that expand into themselves
+const MAX_MACRO_EXPANSIONS: usize = 10_000;
+
+/// Returned by [`assemble`], locating the offending token in the source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssembleError {
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based column, in characters
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for AssembleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for AssembleError {}
+
+/// Assembles Octo source code into ROM bytes to be loaded at 0x200.
+///
+/// Supports the instruction statements of CHIP-8, SUPER-CHIP and XO-CHIP,
+/// labels (`: name`), `:const`, `:alias`, `:call`, `:byte`, `:macro`,
+/// `if ... then`, `if ... begin ... else ... end`, `loop ... while ... again`
+/// and raw bytes. Labels may be used before they are defined.
+pub fn assemble(source: &str) -> Result<Vec<u8>, AssembleError> {
+    Assembler::new(tokenize(source)).assemble()
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    text: String,
+    line: usize,
+    column: usize,
+}
+
+impl Token {
+    fn error(&self, message: impl Into<String>) -> AssembleError {
+        AssembleError {
+            line: self.line,
+            column: self.column,
+            message: message.into(),
+        }
+    }
+}
+
+/// Splits the source into whitespace-separated tokens, dropping `#` comments.
+fn tokenize(source: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+
+    for (line_index, line) in source.lines().enumerate() {
+        let mut current: Option<Token> = None;
+        for (column_index, c) in line.chars().enumerate() {
+            if !c.is_whitespace() {
+                if current.is_none() && c == '#' {
+                    break;
+                }
+                current
+                    .get_or_insert_with(|| Token {
+                        text: String::new(),
+                        line: line_index + 1,
+                        column: column_index + 1,
+                    })
+                    .text
+                    .push(c);
+            } else if let Some(token) = current.take() {
+                tokens.push(token);
+            }
+        }
+        tokens.extend(current);
+    }
+
+    tokens
+}
+
+struct Macro {
+    params: Vec<String>,
+    body: Vec<Token>,
+}
+
+/// A reference to a label that wasn't defined yet when it was used.
+struct Fixup {
+    offset: usize,
+    name: Token,
+    /// `i := long` takes a 16-bit address in the following word
+    long: bool,
+}
+
+struct Loop {
+    token: Token,
+    start: u16,
+    /// Offsets of the jumps emitted by `while`, to be pointed past `again`
+    breaks: Vec<usize>,
+}
+
+struct Assembler {
+    tokens: Vec<Token>,
+    pos: usize,
+    rom: Vec<u8>,
+    labels: HashMap<String, u16>,
+    constants: HashMap<String, i64>,
+    aliases: HashMap<String, u8>,
+    macros: HashMap<String, Macro>,
+    fixups: Vec<Fixup>,
+    /// `if ... begin` blocks with the offset of their pending jump
+    ifs: Vec<(Token, usize)>,
+    loops: Vec<Loop>,
+    expansions: usize,
+}
+
+impl Assembler {
+    fn new(tokens: Vec<Token>) -> Self {
+        Self {
+            tokens,
+            pos: 0,
+            rom: Vec::new(),
+            labels: HashMap::new(),
+            constants: HashMap::new(),
+            aliases: HashMap::new(),
+            macros: HashMap::new(),
+            fixups: Vec::new(),
+            ifs: Vec::new(),
+            loops: Vec::new(),
+            expansions: 0,
+        }
+    }
+
+    fn assemble(mut self) -> Result<Vec<u8>, AssembleError> {
+        while let Some(token) = self.next_token() {
+            self.statement(token)?;
+        }
+
+        if let Some((token, _)) = self.ifs.last() {
+            return Err(token.error("'begin' without a matching 'end'"));
+        }
+        if let Some(open_loop) = self.loops.last() {
+            return Err(open_loop.token.error("'loop' without a matching 'again'"));
+        }
+
+        for fixup in std::mem::take(&mut self.fixups) {
+            let Some(&address) = self.labels.get(&fixup.name.text) else {
+                return Err(fixup
+                    .name
+                    .error(format!("undefined label '{}'", fixup.name.text)));
+            };
+
+            if fixup.long {
+                self.rom[fixup.offset + 2..fixup.offset + 4]
+                    .copy_from_slice(&address.to_be_bytes());
+            } else {
+                if address > 0xFFF {
+                    return Err(fixup.name.error(format!(
+                        "label '{}' at 0x{:04X} is out of range for a 12-bit address",
+                        fixup.name.text, address
+                    )));
+                }
+                self.rom[fixup.offset] |= (address >> 8) as u8;
+                self.rom[fixup.offset + 1] = address as u8;
+            }
+        }
+
+        Ok(self.rom)
+    }
+
+    fn statement(&mut self, token: Token) -> Result<(), AssembleError> {
+        match token.text.as_str() {
+            ":" => {
+                let name = self.expect("a label name", &token)?;
+                self.define(&name)?;
+                let here = self.here(&name)?;
+                self.labels.insert(name.text, here);
+            }
+            ":const" => {
+                let name = self.expect("a constant name", &token)?;
+                let value = self.expect("a value", &name)?;
+                let value = self.value(&value)?;
+                self.define(&name)?;
+                self.constants.insert(name.text, value);
+            }
+            ":alias" => {
+                let name = self.expect("an alias name", &token)?;
+                let register = self.expect("a register", &name)?;
+                let register = self.register(&register)?;
+                self.aliases.insert(name.text, register);
+            }
+            ":macro" => self.define_macro(&token)?,
+            ":call" => {
+                let address = self.expect("an address", &token)?;
+                self.emit_address(0x2000, &address)?;
+            }
+            ":byte" => {
+                let value = self.expect("a byte", &token)?;
+                let byte = self.byte(&value)?;
+                self.rom.push(byte);
+            }
+            "clear" => self.emit(0x00E0),
+            "return" | ";" => self.emit(0x00EE),
+            "scroll-down" => {
+                let n = self.expect_nibble(&token)?;
+                self.emit(0x00C0 | n);
+            }
+            "scroll-up" => {
+                let n = self.expect_nibble(&token)?;
+                self.emit(0x00D0 | n);
+            }
+            "scroll-right" => self.emit(0x00FB),
+            "scroll-left" => self.emit(0x00FC),
+            "exit" => self.emit(0x00FD),
+            "lores" => self.emit(0x00FE),
+            "hires" => self.emit(0x00FF),
+            "jump" => {
+                let address = self.expect("an address", &token)?;
+                self.emit_address(0x1000, &address)?;
+            }
+            "jump0" => {
+                let address = self.expect("an address", &token)?;
+                self.emit_address(0xB000, &address)?;
+            }
+            "i" => self.index_statement(&token)?,
+            "delay" | "buzzer" | "pitch" => {
+                self.expect_text(":=", &token)?;
+                let x = self.expect_register(&token)?;
+                let low = match token.text.as_str() {
+                    "delay" => 0x15,
+                    "buzzer" => 0x18,
+                    _ => 0x3A,
+                };
+                self.emit(0xF000 | x << 8 | low);
+            }
+            "sprite" => {
+                let x = self.expect_register(&token)?;
+                let y = self.expect_register(&token)?;
+                let n = self.expect_nibble(&token)?;
+                self.emit(0xD000 | x << 8 | y << 4 | n);
+            }
+            "bcd" => {
+                let x = self.expect_register(&token)?;
+                self.emit(0xF033 | x << 8);
+            }
+            "save" | "load" => {
+                let x = self.expect_register(&token)?;
+                let is_save = token.text == "save";
+                if self.peek_text() == Some("-") {
+                    self.pos += 1;
+                    let y = self.expect_register(&token)?;
+                    self.emit(if is_save { 0x5002 } else { 0x5003 } | x << 8 | y << 4);
+                } else {
+                    self.emit(if is_save { 0xF055 } else { 0xF065 } | x << 8);
+                }
+            }
+            "saveflags" => {
+                let x = self.expect_register(&token)?;
+                self.emit(0xF075 | x << 8);
+            }
+            "loadflags" => {
+                let x = self.expect_register(&token)?;
+                self.emit(0xF085 | x << 8);
+            }
+            "plane" => {
+                let planes = self.expect("a plane mask", &token)?;
+                let mask = self.value(&planes)?;
+                if !(0..=3).contains(&mask) {
+                    return Err(planes.error("plane mask must be between 0 and 3"));
+                }
+                self.emit(0xF001 | (mask as u16) << 8);
+            }
+            "audio" => self.emit(0xF002),
+            "if" => {
+                let (skip_unless, skip_if) = self.condition(&token)?;
+                let keyword = self.expect("'then' or 'begin'", &token)?;
+                match keyword.text.as_str() {
+                    "then" => self.emit(skip_unless),
+                    "begin" => {
+                        self.emit(skip_if);
+                        self.ifs.push((token, self.rom.len()));
+                        self.emit(0x1000);
+                    }
+                    _ => return Err(keyword.error("expected 'then' or 'begin'")),
+                }
+            }
+            "else" => {
+                let Some((_, offset)) = self.ifs.pop() else {
+                    return Err(token.error("'else' without a matching 'if ... begin'"));
+                };
+                self.ifs.push((token.clone(), self.rom.len()));
+                self.emit(0x1000);
+                self.patch_jump(offset, &token)?;
+            }
+            "end" => {
+                let Some((_, offset)) = self.ifs.pop() else {
+                    return Err(token.error("'end' without a matching 'if ... begin'"));
+                };
+                self.patch_jump(offset, &token)?;
+            }
+            "loop" => {
+                let start = self.here(&token)?;
+                self.loops.push(Loop {
+                    token,
+                    start,
+                    breaks: Vec::new(),
+                });
+            }
+            "while" => {
+                if self.loops.is_empty() {
+                    return Err(token.error("'while' outside of a loop"));
+                }
+                let (_, skip_if) = self.condition(&token)?;
+                self.emit(skip_if);
+                let offset = self.rom.len();
+                self.emit(0x1000);
+                self.loops.last_mut().unwrap().breaks.push(offset);
+            }
+            "again" => {
+                let Some(open_loop) = self.loops.pop() else {
+                    return Err(token.error("'again' without a matching 'loop'"));
+                };
+                if open_loop.start > 0xFFF {
+                    return Err(open_loop
+                        .token
+                        .error("loop start is out of range for a 12-bit address"));
+                }
+                self.emit(0x1000 | open_loop.start);
+                for offset in open_loop.breaks {
+                    self.patch_jump(offset, &token)?;
+                }
+            }
+            text if self.is_register(text) => self.register_statement(&token)?,
+            text if self.macros.contains_key(text) => self.expand_macro(&token)?,
+            text if text.starts_with(':') => {
+                return Err(token.error(format!("unknown directive '{}'", text)));
+            }
+            text if parse_number(text).is_some() => {
+                let byte = self.byte(&token)?;
+                self.rom.push(byte);
+            }
+            // Any other name calls the subroutine at that label
+            _ => self.emit_address(0x2000, &token)?,
+        }
+
+        Ok(())
+    }
+
+    fn index_statement(&mut self, token: &Token) -> Result<(), AssembleError> {
+        let operator = self.expect("':=' or '+='", token)?;
+        match operator.text.as_str() {
+            ":=" => {
+                let operand = self.expect("an address", &operator)?;
+                match operand.text.as_str() {
+                    "hex" => {
+                        let x = self.expect_register(&operand)?;
+                        self.emit(0xF029 | x << 8);
+                    }
+                    "bighex" => {
+                        let x = self.expect_register(&operand)?;
+                        self.emit(0xF030 | x << 8);
+                    }
+                    "long" => {
+                        let address = self.expect("an address", &operand)?;
+                        self.emit(0xF000);
+                        match self.resolve_address(&address)? {
+                            Some(address) => self.emit(address),
+                            None => {
+                                self.fixups.push(Fixup {
+                                    offset: self.rom.len() - 2,
+                                    name: address,
+                                    long: true,
+                                });
+                                self.emit(0x0000);
+                            }
+                        }
+                    }
+                    _ => self.emit_address(0xA000, &operand)?,
+                }
+            }
+            "+=" => {
+                let x = self.expect_register(&operator)?;
+                self.emit(0xF01E | x << 8);
+            }
+            _ => return Err(operator.error("expected ':=' or '+='")),
+        }
+
+        Ok(())
+    }
+
+    fn register_statement(&mut self, token: &Token) -> Result<(), AssembleError> {
+        let x = self.register(token)? as u16;
+        let operator = self.expect("an operator", token)?;
+        let operand = self.expect("an operand", &operator)?;
+        let y = if self.is_register(&operand.text) {
+            Some(self.register(&operand)? as u16)
+        } else {
+            None
+        };
+
+        let opcode = match (operator.text.as_str(), y) {
+            (":=", Some(y)) => 0x8000 | x << 8 | y << 4,
+            (":=", None) => match operand.text.as_str() {
+                "random" => {
+                    let mask = self.expect("a byte", &operand)?;
+                    0xC000 | x << 8 | self.byte(&mask)? as u16
+                }
+                "delay" => 0xF007 | x << 8,
+                "key" => 0xF00A | x << 8,
+                _ => 0x6000 | x << 8 | self.byte(&operand)? as u16,
+            },
+            ("+=", Some(y)) => 0x8004 | x << 8 | y << 4,
+            ("+=", None) => 0x7000 | x << 8 | self.byte(&operand)? as u16,
+            ("-=", Some(y)) => 0x8005 | x << 8 | y << 4,
+            ("-=", None) => 0x7000 | x << 8 | self.byte(&operand)?.wrapping_neg() as u16,
+            ("|=", Some(y)) => 0x8001 | x << 8 | y << 4,
+            ("&=", Some(y)) => 0x8002 | x << 8 | y << 4,
+            ("^=", Some(y)) => 0x8003 | x << 8 | y << 4,
+            (">>=", Some(y)) => 0x8006 | x << 8 | y << 4,
+            ("=-", Some(y)) => 0x8007 | x << 8 | y << 4,
+            ("<<=", Some(y)) => 0x800E | x << 8 | y << 4,
+            ("|=" | "&=" | "^=" | ">>=" | "=-" | "<<=", None) => {
+                return Err(operand.error(format!("expected a register, found '{}'", operand.text)));
+            }
+            (other, _) => {
+                return Err(operator.error(format!("unknown operator '{}'", other)));
+            }
+        };
+
+        self.emit(opcode);
+        Ok(())
+    }
+
+    /// Parses `vx == n`, `vx != vy`, `vx key`, ... and returns the opcodes
+    /// skipping the next instruction when the condition is false and when
+    /// it's true, respectively.
+    fn condition(&mut self, token: &Token) -> Result<(u16, u16), AssembleError> {
+        let x = self.expect_register(token)?;
+        let operator = self.expect("a comparison", token)?;
+
+        let opcodes = match operator.text.as_str() {
+            "key" => (0xE0A1 | x << 8, 0xE09E | x << 8),
+            "-key" => (0xE09E | x << 8, 0xE0A1 | x << 8),
+            "==" | "!=" => {
+                let operand = self.expect("an operand", &operator)?;
+                let (equal, not_equal) = if self.is_register(&operand.text) {
+                    let y = self.register(&operand)? as u16;
+                    (0x5000 | x << 8 | y << 4, 0x9000 | x << 8 | y << 4)
+                } else {
+                    let byte = self.byte(&operand)? as u16;
+                    (0x3000 | x << 8 | byte, 0x4000 | x << 8 | byte)
+                };
+
+                if operator.text == "==" {
+                    (not_equal, equal)
+                } else {
+                    (equal, not_equal)
+                }
+            }
+            other => {
+                return Err(operator.error(format!("unsupported comparison '{}'", other)));
+            }
+        };
+
+        Ok(opcodes)
+    }
+
+    fn define_macro(&mut self, token: &Token) -> Result<(), AssembleError> {
+        let name = self.expect("a macro name", token)?;
+        self.define(&name)?;
+
+        let mut params = Vec::new();
+        loop {
+            let param = self.expect("'{'", &name)?;
+            if param.text == "{" {
+                break;
+            }
+            params.push(param.text);
+        }
+
+        let mut body = Vec::new();
+        let mut depth = 1;
+        loop {
+            let body_token = self.expect("'}'", token)?;
+            match body_token.text.as_str() {
+                "{" => depth += 1,
+                "}" => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                _ => {}
+            }
+            body.push(body_token);
+        }
+
+        self.macros.insert(name.text, Macro { params, body });
+        Ok(())
+    }
+
+    fn expand_macro(&mut self, token: &Token) -> Result<(), AssembleError> {
+        self.expansions += 1;
+        if self.expansions > MAX_MACRO_EXPANSIONS {
+            return Err(token.error(format!(
+                "too many macro expansions, is '{}' recursive?",
+                token.text
+            )));
+        }
+
+        let params = self.macros[&token.text].params.clone();
+        let mut args = HashMap::new();
+        for param in params {
+            let arg = self.expect(&format!("argument '{}'", param), token)?;
+            args.insert(param, arg.text);
+        }
+
+        let body: Vec<Token> = self.macros[&token.text]
+            .body
+            .iter()
+            .map(|body_token| Token {
+                text: args
+                    .get(&body_token.text)
+                    .cloned()
+                    .unwrap_or_else(|| body_token.text.clone()),
+                ..body_token.clone()
+            })
+            .collect();
+        self.tokens.splice(self.pos..self.pos, body);
+
+        Ok(())
+    }
+
+    fn define(&self, name: &Token) -> Result<(), AssembleError> {
+        let text = name.text.as_str();
+        if self.labels.contains_key(text)
+            || self.constants.contains_key(text)
+            || self.macros.contains_key(text)
+        {
+            return Err(name.error(format!("'{}' is already defined", text)));
+        }
+        if parse_number(text).is_some() || self.is_register(text) {
+            return Err(name.error(format!("'{}' can't be used as a name", text)));
+        }
+
+        Ok(())
+    }
+
+    /// Address of the next byte emitted, or an error at `token` once the
+    /// ROM no longer fits in memory.
+    fn here(&self, token: &Token) -> Result<u16, AssembleError> {
+        u16::try_from(ROM_START as usize + self.rom.len())
+            .map_err(|_| token.error("program is too large to fit in memory"))
+    }
+
+    fn emit(&mut self, opcode: u16) {
+        self.rom.extend_from_slice(&opcode.to_be_bytes());
+    }
+
+    /// Emits `opcode` with the 12-bit address named by `token` in its low
+    /// bits, resolving labels defined later once the whole source is read.
+    fn emit_address(&mut self, opcode: u16, token: &Token) -> Result<(), AssembleError> {
+        match self.resolve_address(token)? {
+            Some(address) if address > 0xFFF => Err(token.error(format!(
+                "address 0x{:04X} is out of range for a 12-bit address",
+                address
+            ))),
+            Some(address) => {
+                self.emit(opcode | address);
+                Ok(())
+            }
+            None => {
+                self.fixups.push(Fixup {
+                    offset: self.rom.len(),
+                    name: token.clone(),
+                    long: false,
+                });
+                self.emit(opcode);
+                Ok(())
+            }
+        }
+    }
+
+    /// Resolves a number, constant or already defined label; `None` for a
+    /// label that may be defined later.
+    fn resolve_address(&self, token: &Token) -> Result<Option<u16>, AssembleError> {
+        let text = token.text.as_str();
+        let value = match parse_number(text).or_else(|| self.constants.get(text).copied()) {
+            Some(value) => value,
+            None => return Ok(self.labels.get(text).copied()),
+        };
+
+        u16::try_from(value)
+            .map(Some)
+            .map_err(|_| token.error(format!("address {} is out of range", value)))
+    }
+
+    /// Points the jump at `offset` to the current address.
+    fn patch_jump(&mut self, offset: usize, token: &Token) -> Result<(), AssembleError> {
+        let here = self.here(token)?;
+        if here > 0xFFF {
+            return Err(token.error("jump target is out of range for a 12-bit address"));
+        }
+
+        self.rom[offset..offset + 2].copy_from_slice(&(0x1000 | here).to_be_bytes());
+        Ok(())
+    }
+
+    fn next_token(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn peek_text(&self) -> Option<&str> {
+        self.tokens.get(self.pos).map(|token| token.text.as_str())
+    }
+
+    /// Returns the next token, or an error located at `previous` when the
+    /// source ends.
+    fn expect(&mut self, what: &str, previous: &Token) -> Result<Token, AssembleError> {
+        self.next_token().ok_or_else(|| {
+            previous.error(format!(
+                "expected {} after '{}', found end of input",
+                what, previous.text
+            ))
+        })
+    }
+
+    fn expect_text(&mut self, text: &str, previous: &Token) -> Result<(), AssembleError> {
+        let token = self.expect(&format!("'{}'", text), previous)?;
+        if token.text != text {
+            return Err(token.error(format!("expected '{}', found '{}'", text, token.text)));
+        }
+
+        Ok(())
+    }
+
+    fn expect_register(&mut self, previous: &Token) -> Result<u16, AssembleError> {
+        let token = self.expect("a register", previous)?;
+        Ok(self.register(&token)? as u16)
+    }
+
+    fn expect_nibble(&mut self, previous: &Token) -> Result<u16, AssembleError> {
+        let token = self.expect("a value", previous)?;
+        let value = self.value(&token)?;
+        if !(0..=0xF).contains(&value) {
+            return Err(token.error(format!("value {} is out of range for a nibble", value)));
+        }
+
+        Ok(value as u16)
+    }
+
+    fn is_register(&self, text: &str) -> bool {
+        self.aliases.contains_key(text) || parse_register(text).is_some()
+    }
+
+    fn register(&self, token: &Token) -> Result<u8, AssembleError> {
+        self.aliases
+            .get(&token.text)
+            .copied()
+            .or_else(|| parse_register(&token.text))
+            .ok_or_else(|| token.error(format!("expected a register, found '{}'", token.text)))
+    }
+
+    fn value(&self, token: &Token) -> Result<i64, AssembleError> {
+        parse_number(&token.text)
+            .or_else(|| self.constants.get(&token.text).copied())
+            .ok_or_else(|| token.error(format!("expected a number, found '{}'", token.text)))
+    }
+
+    fn byte(&self, token: &Token) -> Result<u8, AssembleError> {
+        let value = self.value(token)?;
+        if !(-128..=255).contains(&value) {
+            return Err(token.error(format!("value {} is out of range for a byte", value)));
+        }
+
+        Ok(value as u8)
+    }
+}
+
+/// Parses `v0`-`vf` (in either case).
+fn parse_register(text: &str) -> Option<u8> {
+    let digit = text.strip_prefix(['v', 'V'])?;
+    if digit.len() != 1 {
+        return None;
+    }
+
+    u8::from_str_radix(digit, 16).ok()
+}
+
+/// Parses a decimal, `0x` hexadecimal or `0b` binary number, optionally negative.
+fn parse_number(text: &str) -> Option<i64> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text),
+    };
+
+    let value = if let Some(hex) = digits.strip_prefix("0x") {
+        i64::from_str_radix(hex, 16).ok()?
+    } else if let Some(binary) = digits.strip_prefix("0b") {
+        i64::from_str_radix(binary, 2).ok()?
+    } else if digits.starts_with(|c: char| c.is_ascii_digit()) {
+        digits.parse().ok()?
+    } else {
+        return None;
+    };
+
+    Some(if negative { -value } else { value })
+}
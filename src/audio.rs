@@ -0,0 +1,137 @@
+//! Sound synthesis: the buzzer rendered to 16-bit PCM samples that can be
+//! written as a WAV file, without an audio device.
+
+use std::io::{self, Write};
+
+use crate::frontend::Audio;
+
+/// Peak sample value, a quarter of full scale to leave headroom.
+const AMPLITUDE: i16 = i16::MAX / 4;
+
+/// Frequency of the CHIP-8 beep in Hz.
+pub const DEFAULT_BEEP_FREQUENCY: f64 = 440.0;
+
+/// Renders the sound of a machine run with [`crate::Chip8::run_frame_with`].
+///
+/// While the sound timer is non-zero it plays a square wave, or once the
+/// program has loaded a non-silent XO-CHIP audio pattern with F002, that
+/// pattern's 128 bits looped at `4000 * 2^((pitch - 64) / 48)` bits per
+/// second.
+#[derive(Debug, Clone)]
+pub struct Synthesizer {
+    sample_rate: u32,
+    /// Frequency of the square wave in Hz.
+    pub beep_frequency: f64,
+    samples: Vec<i16>,
+    playing: bool,
+    /// Whether the sound played at any point of the current frame, so that
+    /// sounds shorter than a frame are heard.
+    sounded: bool,
+    pattern: [u8; 16],
+    pitch: u8,
+    /// Position in the waveform: cycles of the square wave, or bits of the
+    /// pattern.
+    phase: f64,
+    frames: u64,
+}
+
+impl Synthesizer {
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            beep_frequency: DEFAULT_BEEP_FREQUENCY,
+            samples: Vec::new(),
+            playing: false,
+            sounded: false,
+            pattern: [0; 16],
+            pitch: 64,
+            phase: 0.0,
+            frames: 0,
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// The samples rendered so far, mono.
+    pub fn samples(&self) -> &[i16] {
+        &self.samples
+    }
+
+    /// Renders the samples of one 60 Hz frame; call after each frame.
+    pub fn render_frame(&mut self) {
+        let frame_start = |frame: u64| frame * self.sample_rate as u64 / 60;
+        let len = (frame_start(self.frames + 1) - frame_start(self.frames)) as usize;
+        self.frames += 1;
+
+        if !self.sounded {
+            self.samples.resize(self.samples.len() + len, 0);
+            return;
+        }
+        self.sounded = self.playing;
+
+        let use_pattern = self.pattern.iter().any(|&byte| byte != 0);
+        let step = if use_pattern {
+            4000.0 * 2f64.powf((self.pitch as f64 - 64.0) / 48.0) / self.sample_rate as f64
+        } else {
+            self.beep_frequency / self.sample_rate as f64
+        };
+
+        for _ in 0..len {
+            let high = if use_pattern {
+                let bit = self.phase as usize % 128;
+                self.pattern[bit / 8] & 0x80 >> (bit % 8) != 0
+            } else {
+                self.phase.fract() < 0.5
+            };
+            self.samples.push(if high { AMPLITUDE } else { -AMPLITUDE });
+            self.phase += step;
+        }
+        // Keeps the phase small enough to stay precise
+        self.phase %= if use_pattern { 128.0 } else { 1.0 };
+    }
+
+    /// Writes the samples as a 16-bit mono PCM WAV file.
+    pub fn write_wav<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let data_len = (self.samples.len() * 2) as u32;
+        let byte_rate = self.sample_rate * 2;
+
+        writer.write_all(b"RIFF")?;
+        writer.write_all(&(36 + data_len).to_le_bytes())?;
+        writer.write_all(b"WAVEfmt ")?;
+        writer.write_all(&16u32.to_le_bytes())?;
+        // PCM, 1 channel
+        writer.write_all(&1u16.to_le_bytes())?;
+        writer.write_all(&1u16.to_le_bytes())?;
+        writer.write_all(&self.sample_rate.to_le_bytes())?;
+        writer.write_all(&byte_rate.to_le_bytes())?;
+        // Block align and bits per sample
+        writer.write_all(&2u16.to_le_bytes())?;
+        writer.write_all(&16u16.to_le_bytes())?;
+        writer.write_all(b"data")?;
+        writer.write_all(&data_len.to_le_bytes())?;
+
+        let data: Vec<u8> = self
+            .samples
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes())
+            .collect();
+        writer.write_all(&data)
+    }
+}
+
+impl Audio for Synthesizer {
+    fn set_playing(&mut self, playing: bool) {
+        if playing && !self.sounded {
+            self.phase = 0.0;
+        }
+        self.playing = playing;
+        self.sounded |= playing;
+    }
+
+    fn set_pattern(&mut self, pattern: &[u8; 16], pitch: u8) {
+        self.pattern = *pattern;
+        self.pitch = pitch;
+    }
+}
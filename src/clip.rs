@@ -0,0 +1,173 @@
+//! Gameplay clips: the display captured once per frame as an animated GIF or
+//! a sequence of PPM images.
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Error, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use crate::screenshot::sample;
+use crate::{Chip8, ImageFormat, Palette, Screenshot};
+
+/// Shortest GIF frame delay in hundredths of a second; viewers slow shorter
+/// frames down to 1/10 s.
+const MIN_GIF_DELAY: u64 = 2;
+
+/// Records the display as an animated GIF, looping forever.
+///
+/// Frames that don't change the display extend the previous frame instead of
+/// adding a new one. As GIF delays are in hundredths of a second, frames
+/// shown for less than 2/100 s are dropped.
+pub struct GifRecorder<W: Write> {
+    encoder: Option<gif::Encoder<W>>,
+    writer: Option<W>,
+    scale: usize,
+    palette: Palette,
+    /// Size of the GIF, from the resolution of the first frame; frames at
+    /// the other resolution are resampled to it.
+    size: (usize, usize),
+    /// Pixels of the frame not written yet, and the frame it started on.
+    pending: Option<(Vec<u8>, u64)>,
+    frames: u64,
+}
+
+impl<W: Write> GifRecorder<W> {
+    /// A recorder writing to `writer`, with each display pixel `scale` GIF
+    /// pixels wide and high.
+    pub fn new(writer: W, scale: usize, palette: Palette) -> io::Result<Self> {
+        if scale == 0 || 128 * scale > u16::MAX as usize {
+            return Err(Error::new(ErrorKind::InvalidInput, "invalid scale"));
+        }
+
+        Ok(Self {
+            encoder: None,
+            writer: Some(writer),
+            scale,
+            palette,
+            size: (0, 0),
+            pending: None,
+            frames: 0,
+        })
+    }
+
+    /// Number of frames captured.
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// Captures the display of `chip8` as the next frame.
+    pub fn capture(&mut self, chip8: &Chip8) -> io::Result<()> {
+        if let Some(writer) = self.writer.take() {
+            self.size = (
+                chip8.video_width() * self.scale,
+                chip8.video_height() * self.scale,
+            );
+            let (width, height) = self.size;
+            let mut encoder = gif::Encoder::new(
+                writer,
+                width as u16,
+                height as u16,
+                self.palette.0.as_flattened(),
+            )
+            .map_err(Error::other)?;
+            encoder
+                .set_repeat(gif::Repeat::Infinite)
+                .map_err(Error::other)?;
+            self.encoder = Some(encoder);
+        }
+
+        let pixels = sample(chip8, self.size.0, self.size.1);
+        match self.pending.take() {
+            Some((pending, start)) if pending == pixels => self.pending = Some((pending, start)),
+            Some((pending, start)) if delay(start, self.frames) >= MIN_GIF_DELAY => {
+                self.write_frame(pending, start, self.frames)?;
+                self.pending = Some((pixels, self.frames));
+            }
+            // Replaces a frame too short to show
+            Some((_, start)) => self.pending = Some((pixels, start)),
+            None => self.pending = Some((pixels, self.frames)),
+        }
+        self.frames += 1;
+
+        Ok(())
+    }
+
+    /// Writes the last frame and the GIF trailer, returning the writer.
+    /// Fails if no frame was captured.
+    pub fn finish(mut self) -> io::Result<W> {
+        let Some((pending, start)) = self.pending.take() else {
+            return Err(Error::new(ErrorKind::InvalidInput, "no frames captured"));
+        };
+        self.write_frame(pending, start, self.frames)?;
+
+        let encoder = self.encoder.take().expect("Capturing creates the encoder");
+        encoder.into_inner().map_err(Error::other)
+    }
+
+    fn write_frame(&mut self, pixels: Vec<u8>, start: u64, end: u64) -> io::Result<()> {
+        let (width, height) = self.size;
+        let mut frame = gif::Frame::from_indexed_pixels(width as u16, height as u16, pixels, None);
+        frame.delay = delay(start, end).max(MIN_GIF_DELAY).min(u16::MAX as u64) as u16;
+
+        let encoder = self
+            .encoder
+            .as_mut()
+            .expect("Capturing creates the encoder");
+        encoder.write_frame(&frame).map_err(Error::other)
+    }
+}
+
+/// GIF delay in hundredths of a second from frame `start` to `end`, rounded
+/// so that delays add up to the time at 60 Hz.
+fn delay(start: u64, end: u64) -> u64 {
+    let centiseconds = |frame: u64| (frame * 100 + 30) / 60;
+
+    centiseconds(end) - centiseconds(start)
+}
+
+/// Records the display as numbered PPM images (`000000.ppm`, `000001.ppm`,
+/// ...) in a directory, one per frame.
+pub struct PpmSequence {
+    directory: PathBuf,
+    screenshot: Screenshot,
+    frames: u64,
+}
+
+impl PpmSequence {
+    /// A recorder writing to `directory`, which is created if needed.
+    pub fn new(directory: &Path, scale: usize, palette: Palette) -> io::Result<Self> {
+        if scale == 0 {
+            return Err(Error::new(ErrorKind::InvalidInput, "invalid scale"));
+        }
+        fs::create_dir_all(directory)?;
+
+        Ok(Self {
+            directory: directory.to_path_buf(),
+            screenshot: Screenshot {
+                scale,
+                palette,
+                ..Screenshot::new(ImageFormat::Ppm)
+            },
+            frames: 0,
+        })
+    }
+
+    /// Number of frames captured.
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// Path of the image of `frame`.
+    pub fn frame_path(&self, frame: u64) -> PathBuf {
+        self.directory.join(format!("{:06}.ppm", frame))
+    }
+
+    /// Captures the display of `chip8` as the next frame.
+    pub fn capture(&mut self, chip8: &Chip8) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(self.frame_path(self.frames))?);
+        self.screenshot.write(chip8, &mut file)?;
+        file.flush()?;
+        self.frames += 1;
+
+        Ok(())
+    }
+}
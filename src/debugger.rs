@@ -0,0 +1,564 @@
+//! Interactive debugger: a command line for stepping through a program,
+//! with breakpoints, watchpoints, reverse-step and memory inspection.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::{
+    Chip8, DEFAULT_INSTRUCTIONS_PER_FRAME, ImageFormat, Instruction, Line, Rewind, Screenshot,
+    Syntax, state_slot_path,
+};
+
+const PROMPT: &str = "(hachi) ";
+
+/// Snapshots kept for reverse-step.
+const HISTORY_DEPTH: usize = 10_000;
+
+const HELP: &str = "Commands:
+  step [n]           s  Execute n instructions (default: 1)
+  next               n  Execute one instruction, stepping over calls
+  continue           c  Run until a breakpoint, watchpoint, exit, error or Ctrl-C
+  reverse-step [n]   rs Undo n steps (default: 1); continue is undone a frame at a time
+  break <addr>       b  Stop when pc reaches addr
+  break op <pattern>    Stop before an opcode matching pattern, with ? as wildcard (e.g. D???)
+  watch <addr> [len] w  Stop when memory in addr..addr+len changes (default len: 1)
+  delete <n>            Remove breakpoint or watchpoint n
+  list               l  List breakpoints and watchpoints
+  regs               r  Show registers and timers
+  stack                 Show the call stack
+  mem <addr> [len]   x  Hexdump len bytes of memory (default: 64)
+  disasm [addr] [n]  d  Disassemble n instructions from addr (default: pc, 8)
+  screen                Show the display
+  screenshot <file> [n] Save the display as a .pbm, .ppm or .png image, n times larger
+  press <key>           Hold down a keypad key (0-F)
+  release <key>         Release a keypad key
+  save [slot]           Save the machine state to a numbered slot (default: 0)
+  load [slot]           Restore the machine state from a numbered slot (default: 0)
+  help               h  Show this help
+  quit               q  Exit the debugger
+
+Addresses and keys are hexadecimal; an empty line repeats the last command.";
+
+/// Condition checked while running in the [`Debugger`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Breakpoint {
+    /// Stops when `pc` reaches the address.
+    Address(u16),
+    /// Stops before executing an opcode equal to `value` in the bits set in `mask`.
+    Opcode { mask: u16, value: u16 },
+    /// Stops when any byte of memory in the range changes.
+    Watch { start: usize, len: usize },
+}
+
+impl Breakpoint {
+    /// Parses an opcode pattern such as `D???` or `00E0`.
+    fn opcode(pattern: &str) -> Option<Self> {
+        if pattern.len() != 4 {
+            return None;
+        }
+
+        let (mut mask, mut value) = (0, 0);
+        for c in pattern.chars() {
+            mask <<= 4;
+            value <<= 4;
+            if c != '?' {
+                mask |= 0xF;
+                value |= c.to_digit(16)? as u16;
+            }
+        }
+
+        Some(Self::Opcode { mask, value })
+    }
+}
+
+/// Interactive debugger driving a [`Chip8`] one instruction at a time.
+///
+/// Commands are executed with [`Debugger::command`], or read from an input
+/// stream by [`Debugger::run`].
+pub struct Debugger {
+    pub chip8: Chip8,
+    /// Instructions executed between timer ticks.
+    pub instructions_per_frame: usize,
+    pub breakpoints: BTreeMap<usize, Breakpoint>,
+    /// ROM path the numbered save state slots are named after, see
+    /// [`state_slot_path`].
+    pub state_path: Option<PathBuf>,
+    /// States before each step, and before each frame while running, for
+    /// reverse-step.
+    pub history: Rewind,
+    /// Stops `continue` and `next` when set, e.g. from a Ctrl-C handler.
+    /// Cleared when they start.
+    pub interrupt: Arc<AtomicBool>,
+    next_breakpoint_id: usize,
+    last_command: String,
+}
+
+impl Debugger {
+    pub fn new(chip8: Chip8) -> Self {
+        Self {
+            chip8,
+            instructions_per_frame: DEFAULT_INSTRUCTIONS_PER_FRAME,
+            breakpoints: BTreeMap::new(),
+            state_path: None,
+            history: Rewind::new(HISTORY_DEPTH),
+            interrupt: Arc::new(AtomicBool::new(false)),
+            next_breakpoint_id: 1,
+            last_command: String::new(),
+        }
+    }
+
+    /// Reads commands from `input` until `quit` or the end of input, writing
+    /// a prompt and each command's output to `output`.
+    pub fn run<R: BufRead, W: Write>(&mut self, input: R, mut output: W) -> io::Result<()> {
+        write!(output, "{}\n{}", self.location(), PROMPT)?;
+        output.flush()?;
+
+        for line in input.lines() {
+            let line = line?;
+            if matches!(line.trim(), "quit" | "q") {
+                break;
+            }
+
+            write!(output, "{}{}", self.command(&line), PROMPT)?;
+            output.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Executes one debugger command and returns its output, ending in a
+    /// newline unless empty.
+    pub fn command(&mut self, line: &str) -> String {
+        let line = if line.trim().is_empty() {
+            self.last_command.clone()
+        } else {
+            line.trim().to_string()
+        };
+        self.last_command = line.clone();
+
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let result = match words.as_slice() {
+            [] => Ok(String::new()),
+            ["step" | "s"] => Ok(self.step(1)),
+            ["step" | "s", n] => parse_count(n).map(|n| self.step(n)),
+            ["next" | "n"] => Ok(self.next()),
+            ["continue" | "c"] => Ok(self.resume(None)),
+            ["reverse-step" | "rs"] => Ok(self.reverse_step(1)),
+            ["reverse-step" | "rs", n] => parse_count(n).map(|n| self.reverse_step(n)),
+            ["break" | "b", "op", pattern] => Breakpoint::opcode(pattern)
+                .ok_or_else(|| format!("Invalid opcode pattern: {}", pattern))
+                .map(|breakpoint| self.add_breakpoint(breakpoint)),
+            ["break" | "b", address] => {
+                parse_hex(address).map(|address| self.add_breakpoint(Breakpoint::Address(address)))
+            }
+            ["watch" | "w", address] => parse_hex(address).map(|start| {
+                self.add_breakpoint(Breakpoint::Watch {
+                    start: start as usize,
+                    len: 1,
+                })
+            }),
+            ["watch" | "w", address, len] => parse_hex(address).and_then(|start| {
+                parse_count(len).map(|len| {
+                    self.add_breakpoint(Breakpoint::Watch {
+                        start: start as usize,
+                        len,
+                    })
+                })
+            }),
+            ["delete", id] => parse_count(id).and_then(|id| {
+                self.breakpoints
+                    .remove(&id)
+                    .map(|_| format!("Deleted {}\n", id))
+                    .ok_or_else(|| format!("No breakpoint or watchpoint {}", id))
+            }),
+            ["list" | "l"] => Ok(self.list_breakpoints()),
+            ["regs" | "r"] => Ok(self.registers()),
+            ["stack"] => Ok(self.stack()),
+            ["mem" | "x", address] => parse_hex(address).map(|address| self.hexdump(address, 64)),
+            ["mem" | "x", address, len] => parse_hex(address)
+                .and_then(|address| parse_count(len).map(|len| self.hexdump(address, len))),
+            ["disasm" | "d"] => Ok(self.disassemble(self.chip8.pc, 8)),
+            ["disasm" | "d", address] => {
+                parse_hex(address).map(|address| self.disassemble(address, 8))
+            }
+            ["disasm" | "d", address, n] => parse_hex(address)
+                .and_then(|address| parse_count(n).map(|n| self.disassemble(address, n))),
+            ["screen"] => Ok(self.chip8.video_ascii()),
+            ["screenshot", path] => self.screenshot(path, 1),
+            ["screenshot", path, scale] => {
+                parse_count(scale).and_then(|scale| self.screenshot(path, scale))
+            }
+            ["press", key] => parse_key(key).map(|key| {
+                self.chip8.press_key(key);
+                String::new()
+            }),
+            ["release", key] => parse_key(key).map(|key| {
+                self.chip8.release_key(key);
+                String::new()
+            }),
+            ["save"] => self.save_state(0),
+            ["save", slot] => parse_slot(slot).and_then(|slot| self.save_state(slot)),
+            ["load"] => self.load_state(0),
+            ["load", slot] => parse_slot(slot).and_then(|slot| self.load_state(slot)),
+            ["help" | "h"] => Ok(format!("{}\n", HELP)),
+            _ => Err(format!("Unknown command: {} (try 'help')", line)),
+        };
+
+        result.unwrap_or_else(|e| format!("{}\n", e))
+    }
+
+    /// The instruction at `pc`, e.g. `0x0202: D005  DRW V0, V0, 5`.
+    pub fn location(&self) -> String {
+        let line = Line::decode(&self.chip8.memory, self.chip8.pc);
+        let opcode: String = line
+            .bytes
+            .iter()
+            .map(|byte| format!("{:02X}", byte))
+            .collect();
+
+        format!(
+            "0x{:04X}: {:<8}  {}",
+            line.address,
+            opcode,
+            line.text(Syntax::Cowgod)
+        )
+    }
+
+    fn save_state(&self, slot: u8) -> Result<String, String> {
+        let path = self.state_path.as_ref().ok_or("Save states are disabled")?;
+
+        File::create(state_slot_path(path, slot))
+            .and_then(|mut file| self.chip8.save_state(&mut file))
+            .map(|()| format!("Saved slot {}\n", slot))
+            .map_err(|e| format!("Failed to save slot {}: {}", slot, e))
+    }
+
+    fn load_state(&mut self, slot: u8) -> Result<String, String> {
+        let path = self.state_path.as_ref().ok_or("Save states are disabled")?;
+
+        File::open(state_slot_path(path, slot))
+            .and_then(|file| self.chip8.load_state(file))
+            .map(|()| format!("Loaded slot {}\n{}\n", slot, self.location()))
+            .map_err(|e| format!("Failed to load slot {}: {}", slot, e))
+    }
+
+    fn screenshot(&self, path: &str, scale: usize) -> Result<String, String> {
+        let path = Path::new(path);
+        let format = ImageFormat::from_path(path)
+            .ok_or_else(|| format!("Unknown image format: {}", path.display()))?;
+        let screenshot = Screenshot {
+            scale,
+            ..Screenshot::new(format)
+        };
+
+        screenshot
+            .save(&self.chip8, path)
+            .map(|()| format!("Saved {}\n", path.display()))
+            .map_err(|e| format!("Failed to save {}: {}", path.display(), e))
+    }
+
+    fn step(&mut self, n: usize) -> String {
+        for _ in 0..n {
+            self.history.push(&self.chip8);
+            if let Some(reason) = self.execute_one() {
+                return format!("{}\n{}\n", reason, self.location());
+            }
+        }
+
+        format!("{}\n", self.location())
+    }
+
+    fn reverse_step(&mut self, n: usize) -> String {
+        match self.history.rewind(&mut self.chip8, n) {
+            0 => "No earlier state in the history\n".to_string(),
+            undone => format!("Undid {} step(s)\n{}\n", undone, self.location()),
+        }
+    }
+
+    /// Steps over `CALL`s by running until the subroutine returns.
+    fn next(&mut self) -> String {
+        let pc = self.chip8.pc;
+        let line = Line::decode(&self.chip8.memory, pc);
+        if !matches!(line.instruction, Some(Instruction::Call { .. })) {
+            return self.step(1);
+        }
+
+        let return_to = (self.chip8.sp, pc.wrapping_add(2));
+        self.resume(Some(return_to))
+    }
+
+    /// Runs until a stop condition, or until `return_to` (stack pointer and
+    /// pc) is reached.
+    fn resume(&mut self, return_to: Option<(u8, u16)>) -> String {
+        self.interrupt.store(false, Ordering::Relaxed);
+
+        let mut first = true;
+        loop {
+            // The state before running, then one snapshot per frame
+            if first || self.chip8.frame_cycles == 0 {
+                self.history.push(&self.chip8);
+            }
+            first = false;
+            let pc = self.chip8.pc;
+            let reason = self.execute_one().or_else(|| {
+                if return_to == Some((self.chip8.sp, self.chip8.pc)) {
+                    Some(String::new())
+                } else if self.chip8.pc == pc && self.chip8.opcode == 0x1000 | pc {
+                    Some(format!("Infinite loop at 0x{:04X}", pc))
+                } else if self.chip8.is_waiting_for_key() {
+                    Some("Waiting for a key, use 'press' and 'release'".to_string())
+                } else {
+                    self.breakpoint_hit().or_else(|| {
+                        self.interrupt
+                            .swap(false, Ordering::Relaxed)
+                            .then(|| "Interrupted".to_string())
+                    })
+                }
+            });
+
+            if let Some(reason) = reason {
+                let mut output = reason;
+                if !output.is_empty() {
+                    output.push('\n');
+                }
+                return output + &self.location() + "\n";
+            }
+        }
+    }
+
+    /// Executes one instruction and returns why execution should stop, if it
+    /// should.
+    fn execute_one(&mut self) -> Option<String> {
+        if self.chip8.halted {
+            return Some("Program exited".to_string());
+        }
+
+        let watched: Vec<(usize, Vec<u8>)> = self
+            .breakpoints
+            .iter()
+            .filter_map(|(&id, breakpoint)| match *breakpoint {
+                Breakpoint::Watch { start, len } => Some((id, self.watched_memory(start, len))),
+                _ => None,
+            })
+            .collect();
+
+        if let Err(e) = self.chip8.step(self.instructions_per_frame) {
+            return Some(format!("Error: {}", e));
+        }
+
+        for (id, before) in watched {
+            let Some(&Breakpoint::Watch { start, len }) = self.breakpoints.get(&id) else {
+                continue;
+            };
+            let after = self.watched_memory(start, len);
+            if let Some(offset) = (0..before.len()).find(|&i| before[i] != after[i]) {
+                return Some(format!(
+                    "Watchpoint {}: 0x{:04X} changed from 0x{:02X} to 0x{:02X}",
+                    id,
+                    start + offset,
+                    before[offset],
+                    after[offset]
+                ));
+            }
+        }
+
+        if self.chip8.halted {
+            return Some("Program exited".to_string());
+        }
+
+        None
+    }
+
+    fn watched_memory(&self, start: usize, len: usize) -> Vec<u8> {
+        let memory = &self.chip8.memory;
+        let start = start.min(memory.len());
+        memory[start..start.saturating_add(len).min(memory.len())].to_vec()
+    }
+
+    fn breakpoint_hit(&self) -> Option<String> {
+        let pc = self.chip8.pc;
+        let opcode = self
+            .chip8
+            .memory
+            .get(pc as usize..pc as usize + 2)
+            .map(|word| u16::from_be_bytes([word[0], word[1]]));
+
+        self.breakpoints
+            .iter()
+            .find(|(_, breakpoint)| match **breakpoint {
+                Breakpoint::Address(address) => address == pc,
+                Breakpoint::Opcode { mask, value } => {
+                    opcode.is_some_and(|opcode| opcode & mask == value)
+                }
+                Breakpoint::Watch { .. } => false,
+            })
+            .map(|(id, _)| format!("Breakpoint {}", id))
+    }
+
+    fn add_breakpoint(&mut self, breakpoint: Breakpoint) -> String {
+        let id = self.next_breakpoint_id;
+        self.next_breakpoint_id += 1;
+        let output = format!("{} {}\n", id, describe(&breakpoint));
+        self.breakpoints.insert(id, breakpoint);
+
+        output
+    }
+
+    fn list_breakpoints(&self) -> String {
+        if self.breakpoints.is_empty() {
+            return "No breakpoints or watchpoints\n".to_string();
+        }
+
+        self.breakpoints
+            .iter()
+            .map(|(id, breakpoint)| format!("{} {}\n", id, describe(breakpoint)))
+            .collect()
+    }
+
+    fn registers(&self) -> String {
+        let chip8 = &self.chip8;
+        let mut output = String::new();
+
+        for (i, value) in chip8.registers.iter().enumerate() {
+            let separator = if i % 8 == 7 { '\n' } else { ' ' };
+            write!(output, "V{:X}=0x{:02X}{}", i, value, separator).unwrap();
+        }
+        writeln!(
+            output,
+            "I=0x{:04X} PC=0x{:04X} SP={} DT={} ST={}",
+            chip8.index, chip8.pc, chip8.sp, chip8.delay_timer, chip8.sound_timer
+        )
+        .unwrap();
+
+        output
+    }
+
+    fn stack(&self) -> String {
+        if self.chip8.sp == 0 {
+            return "Stack is empty\n".to_string();
+        }
+
+        self.chip8.stack[..self.chip8.sp as usize]
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(depth, address)| format!("#{} 0x{:04X}\n", depth, address))
+            .collect()
+    }
+
+    fn hexdump(&self, address: u16, len: usize) -> String {
+        let memory = &self.chip8.memory;
+        let start = (address as usize).min(memory.len());
+        let end = start.saturating_add(len).min(memory.len());
+        let mut output = String::new();
+
+        for (row, bytes) in memory[start..end].chunks(16).enumerate() {
+            let hex: Vec<String> = bytes.iter().map(|byte| format!("{:02X}", byte)).collect();
+            let ascii: String = bytes
+                .iter()
+                .map(|&byte| {
+                    if byte.is_ascii_graphic() {
+                        byte as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            writeln!(
+                output,
+                "0x{:04X}: {:<47}  |{}|",
+                start + row * 16,
+                hex.join(" "),
+                ascii
+            )
+            .unwrap();
+        }
+
+        output
+    }
+
+    fn disassemble(&self, address: u16, n: usize) -> String {
+        let mut output = String::new();
+        let mut address = address;
+
+        for _ in 0..n {
+            let line = Line::decode(&self.chip8.memory, address);
+            if line.bytes.is_empty() {
+                break;
+            }
+
+            let marker = if address == self.chip8.pc { "=>" } else { "  " };
+            let opcode: String = line
+                .bytes
+                .iter()
+                .map(|byte| format!("{:02X}", byte))
+                .collect();
+            writeln!(
+                output,
+                "{} 0x{:04X}: {:<8}  {}",
+                marker,
+                address,
+                opcode,
+                line.text(Syntax::Cowgod)
+            )
+            .unwrap();
+            address = address.wrapping_add(line.bytes.len() as u16);
+        }
+
+        output
+    }
+}
+
+fn describe(breakpoint: &Breakpoint) -> String {
+    match *breakpoint {
+        Breakpoint::Address(address) => format!("breakpoint at 0x{:04X}", address),
+        Breakpoint::Opcode { mask, value } => {
+            let pattern: String = (0..4)
+                .rev()
+                .map(|nibble| {
+                    if mask >> (nibble * 4) & 0xF == 0 {
+                        '?'
+                    } else {
+                        char::from_digit((value >> (nibble * 4) & 0xF) as u32, 16)
+                            .unwrap()
+                            .to_ascii_uppercase()
+                    }
+                })
+                .collect();
+            format!("breakpoint on opcode {}", pattern)
+        }
+        Breakpoint::Watch { start, len } => {
+            format!(
+                "watchpoint on 0x{:04X}..0x{:04X}",
+                start,
+                start.saturating_add(len)
+            )
+        }
+    }
+}
+
+fn parse_hex(text: &str) -> Result<u16, String> {
+    let digits = text.strip_prefix("0x").unwrap_or(text);
+    u16::from_str_radix(digits, 16).map_err(|_| format!("Invalid address: {}", text))
+}
+
+fn parse_count(text: &str) -> Result<usize, String> {
+    text.parse()
+        .map_err(|_| format!("Invalid number: {}", text))
+}
+
+fn parse_slot(text: &str) -> Result<u8, String> {
+    text.parse().map_err(|_| format!("Invalid slot: {}", text))
+}
+
+fn parse_key(text: &str) -> Result<u8, String> {
+    u8::from_str_radix(text, 16)
+        .ok()
+        .filter(|&key| key < 16)
+        .ok_or_else(|| format!("Invalid key: {}", text))
+}
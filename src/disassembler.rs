@@ -0,0 +1,373 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use crate::{Instruction, decode};
+
+// Bytes shown per line of data between instructions
+const DATA_BYTES_PER_LINE: usize = 8;
+
+/// Assembly syntax used by [`Disassembly::listing`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Syntax {
+    /// Mnemonics from Cowgod's technical reference, e.g. `LD V0, 0x12`.
+    #[default]
+    Cowgod,
+    /// Octo assembly language, e.g. `v0 := 0x12`.
+    Octo,
+}
+
+/// One line of a [`Disassembly`]: an instruction, or data bytes that were
+/// never reached as code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    pub address: u16,
+    pub bytes: Vec<u8>,
+    /// `None` for data
+    pub instruction: Option<Instruction>,
+}
+
+/// The result of [`disassemble`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Disassembly {
+    pub lines: Vec<Line>,
+    /// Names of the addresses targeted by jumps, calls and `LD I` within the
+    /// disassembled range.
+    pub labels: BTreeMap<u16, String>,
+}
+
+/// Disassembles the `len` bytes of `memory` starting at `start`, typically
+/// `chip8.memory` after loading a ROM of `len` bytes at 0x200.
+///
+/// Code is told apart from data by following the control flow from `start`
+/// through jumps, calls and skips. Bytes that are never reached, such as
+/// sprites, are listed as data. Indirect jumps (`JP V0, addr`) aren't followed.
+pub fn disassemble(memory: &[u8], start: u16, len: usize) -> Disassembly {
+    let end = (start as usize + len).min(memory.len());
+    let code = trace_code(memory, start as usize, end);
+    let labels = collect_labels(&code, start, end);
+
+    let mut lines = Vec::new();
+    let mut address = start as usize;
+    while address < end {
+        if let Some(&instruction) = code.get(&address) {
+            let len = instruction_len(instruction);
+            lines.push(Line {
+                address: address as u16,
+                bytes: memory[address..address + len].to_vec(),
+                instruction: Some(instruction),
+            });
+            address += len;
+            continue;
+        }
+
+        let data_start = address;
+        address += 1;
+        while address < end
+            && address - data_start < DATA_BYTES_PER_LINE
+            && !code.contains_key(&address)
+            && !labels.contains_key(&(address as u16))
+        {
+            address += 1;
+        }
+        lines.push(Line {
+            address: data_start as u16,
+            bytes: memory[data_start..address].to_vec(),
+            instruction: None,
+        });
+    }
+
+    Disassembly { lines, labels }
+}
+
+fn instruction_len(instruction: Instruction) -> usize {
+    if instruction == Instruction::LoadLongIndex {
+        4
+    } else {
+        2
+    }
+}
+
+/// Decodes every instruction reachable from `start`, keyed by address.
+fn trace_code(memory: &[u8], start: usize, end: usize) -> BTreeMap<usize, Instruction> {
+    let mut code = BTreeMap::new();
+    let mut pending = vec![start];
+
+    while let Some(mut address) = pending.pop() {
+        while address + 2 <= end && !code.contains_key(&address) {
+            let Ok(instruction) =
+                decode(u16::from_be_bytes([memory[address], memory[address + 1]]))
+            else {
+                break;
+            };
+            let next = address + instruction_len(instruction);
+            if next > end {
+                break;
+            }
+            code.insert(address, instruction);
+
+            match instruction {
+                Instruction::Jump { address } => {
+                    pending.push(address as usize);
+                    break;
+                }
+                Instruction::Call { address } => pending.push(address as usize),
+                Instruction::Return | Instruction::Exit | Instruction::JumpV0 { .. } => break,
+                Instruction::SkipEqualByte { .. }
+                | Instruction::SkipNotEqualByte { .. }
+                | Instruction::SkipEqual { .. }
+                | Instruction::SkipNotEqual { .. }
+                | Instruction::SkipKeyPressed { .. }
+                | Instruction::SkipKeyNotPressed { .. } => {
+                    let is_long_instruction = memory.get(next..next + 2) == Some(&[0xF0, 0x00]);
+                    pending.push(next + if is_long_instruction { 4 } else { 2 });
+                }
+                _ => {}
+            }
+
+            address = next;
+        }
+    }
+
+    code
+}
+
+fn collect_labels(
+    code: &BTreeMap<usize, Instruction>,
+    start: u16,
+    end: usize,
+) -> BTreeMap<u16, String> {
+    let mut calls = BTreeSet::new();
+    let mut jumps = BTreeSet::new();
+    let mut data = BTreeSet::new();
+    for &instruction in code.values() {
+        match instruction {
+            Instruction::Call { address } => calls.insert(address),
+            Instruction::Jump { address } => jumps.insert(address),
+            Instruction::LoadIndex { address } => data.insert(address),
+            _ => false,
+        };
+    }
+
+    let mut labels = BTreeMap::new();
+    let in_range = |address: &u16| (start as usize..end).contains(&(*address as usize));
+    // Earlier kinds take precedence when an address is targeted in several ways
+    for (prefix, addresses) in [("sub", calls), ("label", jumps), ("data", data)] {
+        for address in addresses.into_iter().filter(in_range) {
+            labels
+                .entry(address)
+                .or_insert_with(|| format!("{}_{:03X}", prefix, address));
+        }
+    }
+    labels.insert(start, "main".to_string());
+
+    labels
+}
+
+impl Disassembly {
+    /// Renders the disassembly with one line per instruction or run of data:
+    /// address, raw bytes and mnemonic, preceded by any label.
+    pub fn listing(&self, syntax: Syntax) -> String {
+        let mut listing = String::new();
+
+        for line in &self.lines {
+            if let Some(label) = self.labels.get(&line.address) {
+                match syntax {
+                    Syntax::Cowgod => writeln!(listing, "{}:", label),
+                    Syntax::Octo => writeln!(listing, ": {}", label),
+                }
+                .unwrap();
+            }
+
+            let bytes: Vec<String> = line
+                .bytes
+                .iter()
+                .map(|byte| format!("{:02X}", byte))
+                .collect();
+            let text = line.text_with(syntax, |address| self.address_text(address));
+            writeln!(
+                listing,
+                "{:04X}  {:<w$}  {}",
+                line.address,
+                bytes.join(" "),
+                text,
+                w = DATA_BYTES_PER_LINE * 3 - 1
+            )
+            .unwrap();
+        }
+
+        listing
+    }
+
+    fn address_text(&self, address: u16) -> String {
+        self.labels
+            .get(&address)
+            .cloned()
+            .unwrap_or_else(|| format!("0x{:03X}", address))
+    }
+}
+
+impl Line {
+    /// Decodes the single instruction at `address` without following the
+    /// control flow; bytes that don't decode are returned as data.
+    pub fn decode(memory: &[u8], address: u16) -> Self {
+        let start = (address as usize).min(memory.len());
+        let available = memory.len() - start;
+        let instruction = memory
+            .get(start..start + 2)
+            .and_then(|word| decode(u16::from_be_bytes([word[0], word[1]])).ok())
+            .filter(|&instruction| instruction_len(instruction) <= available);
+        let len = instruction.map_or(available.min(2), instruction_len);
+
+        Self {
+            address,
+            bytes: memory[start..start + len].to_vec(),
+            instruction,
+        }
+    }
+
+    /// The mnemonic, or data directive, with numeric addresses.
+    pub fn text(&self, syntax: Syntax) -> String {
+        self.text_with(syntax, |address| format!("0x{:03X}", address))
+    }
+
+    fn text_with(&self, syntax: Syntax, address_text: impl Fn(u16) -> String) -> String {
+        let Some(instruction) = self.instruction else {
+            return data_text(syntax, &self.bytes);
+        };
+        let long_address = || address_text(u16::from_be_bytes([self.bytes[2], self.bytes[3]]));
+
+        match syntax {
+            Syntax::Cowgod => cowgod(instruction, &address_text, long_address),
+            Syntax::Octo => octo(instruction, &address_text, long_address),
+        }
+    }
+}
+
+fn data_text(syntax: Syntax, bytes: &[u8]) -> String {
+    let bytes: Vec<String> = bytes.iter().map(|byte| format!("0x{:02X}", byte)).collect();
+
+    match syntax {
+        Syntax::Cowgod => format!("DB {}", bytes.join(", ")),
+        Syntax::Octo => bytes.join(" "),
+    }
+}
+
+fn cowgod(
+    instruction: Instruction,
+    address: impl Fn(u16) -> String,
+    long_address: impl Fn() -> String,
+) -> String {
+    match instruction {
+        Instruction::ScrollDown { n } => format!("SCD {}", n),
+        Instruction::ScrollUp { n } => format!("SCU {}", n),
+        Instruction::ClearDisplay => "CLS".to_string(),
+        Instruction::Return => "RET".to_string(),
+        Instruction::ScrollRight => "SCR".to_string(),
+        Instruction::ScrollLeft => "SCL".to_string(),
+        Instruction::Exit => "EXIT".to_string(),
+        Instruction::LowRes => "LOW".to_string(),
+        Instruction::HighRes => "HIGH".to_string(),
+        Instruction::Jump { address: a } => format!("JP {}", address(a)),
+        Instruction::Call { address: a } => format!("CALL {}", address(a)),
+        Instruction::SkipEqualByte { x, byte } => format!("SE V{:X}, 0x{:02X}", x, byte),
+        Instruction::SkipNotEqualByte { x, byte } => format!("SNE V{:X}, 0x{:02X}", x, byte),
+        Instruction::SkipEqual { x, y } => format!("SE V{:X}, V{:X}", x, y),
+        Instruction::StoreRange { x, y } => format!("SAVE V{:X} - V{:X}", x, y),
+        Instruction::LoadRange { x, y } => format!("LOAD V{:X} - V{:X}", x, y),
+        Instruction::LoadByte { x, byte } => format!("LD V{:X}, 0x{:02X}", x, byte),
+        Instruction::AddByte { x, byte } => format!("ADD V{:X}, 0x{:02X}", x, byte),
+        Instruction::Load { x, y } => format!("LD V{:X}, V{:X}", x, y),
+        Instruction::Or { x, y } => format!("OR V{:X}, V{:X}", x, y),
+        Instruction::And { x, y } => format!("AND V{:X}, V{:X}", x, y),
+        Instruction::Xor { x, y } => format!("XOR V{:X}, V{:X}", x, y),
+        Instruction::Add { x, y } => format!("ADD V{:X}, V{:X}", x, y),
+        Instruction::Sub { x, y } => format!("SUB V{:X}, V{:X}", x, y),
+        Instruction::ShiftRight { x, y } => format!("SHR V{:X}, V{:X}", x, y),
+        Instruction::SubN { x, y } => format!("SUBN V{:X}, V{:X}", x, y),
+        Instruction::ShiftLeft { x, y } => format!("SHL V{:X}, V{:X}", x, y),
+        Instruction::SkipNotEqual { x, y } => format!("SNE V{:X}, V{:X}", x, y),
+        Instruction::LoadIndex { address: a } => format!("LD I, {}", address(a)),
+        Instruction::JumpV0 { address: a } => format!("JP V0, {}", address(a)),
+        Instruction::Random { x, byte } => format!("RND V{:X}, 0x{:02X}", x, byte),
+        Instruction::DrawSprite { x, y, n } => format!("DRW V{:X}, V{:X}, {}", x, y, n),
+        Instruction::SkipKeyPressed { x } => format!("SKP V{:X}", x),
+        Instruction::SkipKeyNotPressed { x } => format!("SKNP V{:X}", x),
+        Instruction::LoadLongIndex => format!("LD I, long {}", long_address()),
+        Instruction::SelectPlanes { planes } => format!("PLANE {}", planes),
+        Instruction::LoadAudioPattern => "AUDIO".to_string(),
+        Instruction::LoadDelayTimer { x } => format!("LD V{:X}, DT", x),
+        Instruction::WaitKey { x } => format!("LD V{:X}, K", x),
+        Instruction::SetDelayTimer { x } => format!("LD DT, V{:X}", x),
+        Instruction::SetSoundTimer { x } => format!("LD ST, V{:X}", x),
+        Instruction::AddIndex { x } => format!("ADD I, V{:X}", x),
+        Instruction::LoadFont { x } => format!("LD F, V{:X}", x),
+        Instruction::LoadBigFont { x } => format!("LD HF, V{:X}", x),
+        Instruction::SetPitch { x } => format!("PITCH V{:X}", x),
+        Instruction::StoreBcd { x } => format!("LD B, V{:X}", x),
+        Instruction::StoreRegisters { x } => format!("LD [I], V{:X}", x),
+        Instruction::LoadRegisters { x } => format!("LD V{:X}, [I]", x),
+        Instruction::StoreFlags { x } => format!("LD R, V{:X}", x),
+        Instruction::LoadFlags { x } => format!("LD V{:X}, R", x),
+    }
+}
+
+fn octo(
+    instruction: Instruction,
+    address: impl Fn(u16) -> String,
+    long_address: impl Fn() -> String,
+) -> String {
+    match instruction {
+        Instruction::ScrollDown { n } => format!("scroll-down {}", n),
+        Instruction::ScrollUp { n } => format!("scroll-up {}", n),
+        Instruction::ClearDisplay => "clear".to_string(),
+        Instruction::Return => "return".to_string(),
+        Instruction::ScrollRight => "scroll-right".to_string(),
+        Instruction::ScrollLeft => "scroll-left".to_string(),
+        Instruction::Exit => "exit".to_string(),
+        Instruction::LowRes => "lores".to_string(),
+        Instruction::HighRes => "hires".to_string(),
+        Instruction::Jump { address: a } => format!("jump {}", address(a)),
+        Instruction::Call { address: a } => format!(":call {}", address(a)),
+        // Octo conditionals execute the next instruction when they hold,
+        // so they are the negation of the skip condition
+        Instruction::SkipEqualByte { x, byte } => format!("if v{:x} != 0x{:02X} then", x, byte),
+        Instruction::SkipNotEqualByte { x, byte } => format!("if v{:x} == 0x{:02X} then", x, byte),
+        Instruction::SkipEqual { x, y } => format!("if v{:x} != v{:x} then", x, y),
+        Instruction::StoreRange { x, y } => format!("save v{:x} - v{:x}", x, y),
+        Instruction::LoadRange { x, y } => format!("load v{:x} - v{:x}", x, y),
+        Instruction::LoadByte { x, byte } => format!("v{:x} := 0x{:02X}", x, byte),
+        Instruction::AddByte { x, byte } => format!("v{:x} += 0x{:02X}", x, byte),
+        Instruction::Load { x, y } => format!("v{:x} := v{:x}", x, y),
+        Instruction::Or { x, y } => format!("v{:x} |= v{:x}", x, y),
+        Instruction::And { x, y } => format!("v{:x} &= v{:x}", x, y),
+        Instruction::Xor { x, y } => format!("v{:x} ^= v{:x}", x, y),
+        Instruction::Add { x, y } => format!("v{:x} += v{:x}", x, y),
+        Instruction::Sub { x, y } => format!("v{:x} -= v{:x}", x, y),
+        Instruction::ShiftRight { x, y } => format!("v{:x} >>= v{:x}", x, y),
+        Instruction::SubN { x, y } => format!("v{:x} =- v{:x}", x, y),
+        Instruction::ShiftLeft { x, y } => format!("v{:x} <<= v{:x}", x, y),
+        Instruction::SkipNotEqual { x, y } => format!("if v{:x} == v{:x} then", x, y),
+        Instruction::LoadIndex { address: a } => format!("i := {}", address(a)),
+        Instruction::JumpV0 { address: a } => format!("jump0 {}", address(a)),
+        Instruction::Random { x, byte } => format!("v{:x} := random 0x{:02X}", x, byte),
+        Instruction::DrawSprite { x, y, n } => format!("sprite v{:x} v{:x} {}", x, y, n),
+        Instruction::SkipKeyPressed { x } => format!("if v{:x} -key then", x),
+        Instruction::SkipKeyNotPressed { x } => format!("if v{:x} key then", x),
+        Instruction::LoadLongIndex => format!("i := long {}", long_address()),
+        Instruction::SelectPlanes { planes } => format!("plane {}", planes),
+        Instruction::LoadAudioPattern => "audio".to_string(),
+        Instruction::LoadDelayTimer { x } => format!("v{:x} := delay", x),
+        Instruction::WaitKey { x } => format!("v{:x} := key", x),
+        Instruction::SetDelayTimer { x } => format!("delay := v{:x}", x),
+        Instruction::SetSoundTimer { x } => format!("buzzer := v{:x}", x),
+        Instruction::AddIndex { x } => format!("i += v{:x}", x),
+        Instruction::LoadFont { x } => format!("i := hex v{:x}", x),
+        Instruction::LoadBigFont { x } => format!("i := bighex v{:x}", x),
+        Instruction::SetPitch { x } => format!("pitch := v{:x}", x),
+        Instruction::StoreBcd { x } => format!("bcd v{:x}", x),
+        Instruction::StoreRegisters { x } => format!("save v{:x}", x),
+        Instruction::LoadRegisters { x } => format!("load v{:x}", x),
+        Instruction::StoreFlags { x } => format!("saveflags v{:x}", x),
+        Instruction::LoadFlags { x } => format!("loadflags v{:x}", x),
+    }
+}
@@ -0,0 +1,58 @@
+use std::fmt;
+
+use crate::DecodeError;
+
+/// What went wrong while executing an instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Chip8ErrorKind {
+    /// `CALL` with all 16 stack slots in use.
+    StackOverflow,
+    /// `RET` with an empty stack.
+    StackUnderflow,
+    /// The opcode doesn't decode to any instruction.
+    InvalidOpcode,
+    /// An instruction or fetch touched an address past the end of memory.
+    MemoryOutOfBounds { address: usize },
+}
+
+impl fmt::Display for Chip8ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::StackOverflow => write!(f, "stack overflow"),
+            Self::StackUnderflow => write!(f, "stack underflow"),
+            Self::InvalidOpcode => write!(f, "invalid opcode"),
+            Self::MemoryOutOfBounds { address } => {
+                write!(f, "memory access out of bounds at 0x{:04X}", address)
+            }
+        }
+    }
+}
+
+impl From<DecodeError> for Chip8ErrorKind {
+    fn from(_: DecodeError) -> Self {
+        Self::InvalidOpcode
+    }
+}
+
+/// Error returned by [`Chip8::cycle`](crate::Chip8::cycle), locating the
+/// faulting instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Chip8Error {
+    pub kind: Chip8ErrorKind,
+    /// Address of the faulting instruction
+    pub pc: u16,
+    /// Opcode of the faulting instruction, or 0 if it couldn't be fetched
+    pub opcode: u16,
+}
+
+impl fmt::Display for Chip8Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at 0x{:03X} (opcode 0x{:04X})",
+            self.kind, self.pc, self.opcode
+        )
+    }
+}
+
+impl std::error::Error for Chip8Error {}
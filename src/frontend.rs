@@ -0,0 +1,52 @@
+//! Backends a frontend plugs into [`Chip8::run_frame_with`]: a display sink,
+//! an input source and an audio sink.
+//!
+//! `()` implements each trait as a backend that does nothing, for frontends
+//! that only need some of them.
+
+use crate::Chip8;
+
+/// Receives the display after it changes.
+pub trait Display {
+    /// Called at the end of each frame in which the display was cleared,
+    /// drawn to, scrolled or switched resolution, and on the first frame.
+    /// Read the pixels with [`Chip8::video_width`], [`Chip8::video_height`]
+    /// and `chip8.video`.
+    fn present(&mut self, chip8: &Chip8);
+}
+
+/// Provides the keypad state.
+pub trait Input {
+    /// Called at the start of each frame: the keys held down, indexed by
+    /// keypad key, or `None` to leave the keypad as it is. Keys that changed
+    /// are pressed or released with [`Chip8::set_keypad`].
+    fn keypad(&mut self) -> Option<[bool; 16]>;
+}
+
+/// Plays the sound.
+pub trait Audio {
+    /// Called when the sound timer starts (`true`) or runs out (`false`).
+    fn set_playing(&mut self, playing: bool);
+
+    /// Called when the XO-CHIP audio pattern or pitch changes, and on the
+    /// first frame.
+    fn set_pattern(&mut self, pattern: &[u8; 16], pitch: u8) {
+        let _ = (pattern, pitch);
+    }
+}
+
+impl Display for () {
+    fn present(&mut self, _: &Chip8) {}
+}
+
+/// Leaves the keypad as it is, e.g. when keys are pressed directly with
+/// [`Chip8::press_key`].
+impl Input for () {
+    fn keypad(&mut self) -> Option<[bool; 16]> {
+        None
+    }
+}
+
+impl Audio for () {
+    fn set_playing(&mut self, _: bool) {}
+}
@@ -0,0 +1,362 @@
+//! GDB remote stub: serves the machine over TCP with the GDB Remote Serial
+//! Protocol, so that GDB and compatible debuggers can attach to it.
+
+use std::collections::BTreeSet;
+use std::io::{self, BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::TcpStream;
+
+use crate::{Chip8, Chip8ErrorKind, DEFAULT_INSTRUCTIONS_PER_FRAME};
+
+const PACKET_SIZE: usize = 0x1000;
+// How many instructions `continue` runs between checks for a GDB interrupt
+const INTERRUPT_CHECK_INTERVAL: usize = 1024;
+const INTERRUPT: u8 = 0x03;
+
+// Register numbers: V0-VF, then I, PC, SP, DT and ST
+const REGISTER_I: usize = 16;
+const REGISTER_PC: usize = 17;
+const REGISTER_SP: usize = 18;
+const REGISTER_DT: usize = 19;
+const REGISTER_ST: usize = 20;
+const REGISTER_COUNT: usize = 21;
+
+// Signals reported in stop replies
+const SIGINT: u8 = 2;
+const SIGILL: u8 = 4;
+const SIGTRAP: u8 = 5;
+const SIGSEGV: u8 = 11;
+
+/// GDB Remote Serial Protocol stub exposing a [`Chip8`] to debuggers.
+///
+/// Registers are numbered V0-VF (0-15, 8 bits), I (16, 16 bits),
+/// PC (17, 16 bits), SP (18), DT (19) and ST (20, 8 bits each), with 16-bit
+/// registers sent big-endian like CHIP-8 memory. The layout is also served
+/// as `target.xml`.
+pub struct GdbStub {
+    pub chip8: Chip8,
+    /// Instructions executed between timer ticks.
+    pub instructions_per_frame: usize,
+    pub breakpoints: BTreeSet<u16>,
+    no_ack: bool,
+}
+
+impl GdbStub {
+    pub fn new(chip8: Chip8) -> Self {
+        Self {
+            chip8,
+            instructions_per_frame: DEFAULT_INSTRUCTIONS_PER_FRAME,
+            breakpoints: BTreeSet::new(),
+            no_ack: false,
+        }
+    }
+
+    /// Serves one GDB connection until the debugger detaches, kills the
+    /// target or disconnects.
+    pub fn serve(&mut self, stream: TcpStream) -> io::Result<()> {
+        // Acks and replies are tiny writes, don't let Nagle delay them
+        stream.set_nodelay(true)?;
+        let mut writer = stream.try_clone()?;
+        let mut reader = BufReader::new(stream);
+        self.no_ack = false;
+
+        while let Some(packet) = self.read_packet(&mut reader, &mut writer)? {
+            let reply = match packet.as_str() {
+                "D" => {
+                    self.send_packet(&mut writer, "OK")?;
+                    return Ok(());
+                }
+                "k" => return Ok(()),
+                "c" => self.resume(&mut reader)?,
+                "s" => self.execute_one().unwrap_or_else(|| stop_reply(SIGTRAP)),
+                _ => self.handle(&packet),
+            };
+
+            self.send_packet(&mut writer, &reply)?;
+        }
+
+        Ok(())
+    }
+
+    /// Replies to the packets that don't run the program.
+    fn handle(&mut self, packet: &str) -> String {
+        let result = match packet.split_at_checked(1).unwrap_or(("", packet)) {
+            ("?", _) => Some(stop_reply(SIGTRAP)),
+            ("g", "") => Some(encode_hex(&self.register_bytes())),
+            ("G", data) => decode_hex(data).and_then(|bytes| self.write_registers(&bytes)),
+            ("p", number) => usize::from_str_radix(number, 16)
+                .ok()
+                .and_then(|number| self.register(number))
+                .map(|bytes| encode_hex(&bytes)),
+            ("P", assignment) => assignment.split_once('=').and_then(|(number, value)| {
+                let number = usize::from_str_radix(number, 16).ok()?;
+                self.write_register(number, &decode_hex(value)?)
+            }),
+            ("m", range) => parse_range(range).and_then(|(address, len)| {
+                let memory = self.chip8.memory.get(address..)?;
+                if memory.is_empty() {
+                    return None;
+                }
+                // Each byte takes two hex digits
+                let len = len.min(memory.len()).min(PACKET_SIZE / 2);
+                Some(encode_hex(&memory[..len]))
+            }),
+            ("M", write) => write.split_once(':').and_then(|(range, data)| {
+                let (address, len) = parse_range(range)?;
+                let bytes = decode_hex(data).filter(|bytes| bytes.len() == len)?;
+                self.chip8
+                    .memory
+                    .get_mut(address..address.checked_add(len)?)?
+                    .copy_from_slice(&bytes);
+                Some("OK".to_string())
+            }),
+            ("Z" | "z", breakpoint) => match breakpoint.split(',').collect::<Vec<_>>()[..] {
+                // Hardware breakpoints are handled like software ones
+                ["0" | "1", address, ..] => u16::from_str_radix(address, 16).ok().map(|address| {
+                    if packet.starts_with('Z') {
+                        self.breakpoints.insert(address);
+                    } else {
+                        self.breakpoints.remove(&address);
+                    }
+                    "OK".to_string()
+                }),
+                // Watchpoints aren't supported
+                _ => return String::new(),
+            },
+            _ => return self.query(packet),
+        };
+
+        result.unwrap_or_else(|| "E01".to_string())
+    }
+
+    fn query(&mut self, packet: &str) -> String {
+        if packet.starts_with("qSupported") {
+            return format!(
+                "PacketSize={:x};qXfer:features:read+;swbreak+;QStartNoAckMode+",
+                PACKET_SIZE
+            );
+        }
+        if let Some(range) = packet.strip_prefix("qXfer:features:read:target.xml:") {
+            let Some((offset, len)) = parse_range(range) else {
+                return "E01".to_string();
+            };
+            let xml = target_xml();
+            let chunk = xml.get(offset.min(xml.len())..).unwrap_or_default();
+            return if chunk.len() > len {
+                format!("m{}", &chunk[..len])
+            } else {
+                format!("l{}", chunk)
+            };
+        }
+
+        match packet {
+            "QStartNoAckMode" => {
+                self.no_ack = true;
+                "OK".to_string()
+            }
+            "qAttached" => "1".to_string(),
+            "qC" => "QC1".to_string(),
+            "qfThreadInfo" => "m1".to_string(),
+            "qsThreadInfo" => "l".to_string(),
+            _ if packet.starts_with('H') => "OK".to_string(),
+            // An empty reply tells GDB the packet isn't supported
+            _ => String::new(),
+        }
+    }
+
+    /// Runs until a breakpoint, exit, error or interrupt from GDB.
+    fn resume(&mut self, reader: &mut BufReader<TcpStream>) -> io::Result<String> {
+        let mut cycles = 0usize;
+        loop {
+            if let Some(reply) = self.execute_one() {
+                return Ok(reply);
+            }
+            cycles += 1;
+            if self.breakpoints.contains(&self.chip8.pc) {
+                return Ok(stop_reply(SIGTRAP));
+            }
+            if cycles.is_multiple_of(INTERRUPT_CHECK_INTERVAL) && interrupted(reader)? {
+                return Ok(stop_reply(SIGINT));
+            }
+        }
+    }
+
+    /// Executes one instruction and returns the stop reply if the program
+    /// exited or faulted.
+    fn execute_one(&mut self) -> Option<String> {
+        if self.chip8.halted {
+            return Some("W00".to_string());
+        }
+
+        match self.chip8.step(self.instructions_per_frame) {
+            Err(e) if e.kind == Chip8ErrorKind::InvalidOpcode => Some(stop_reply(SIGILL)),
+            Err(_) => Some(stop_reply(SIGSEGV)),
+            Ok(()) if self.chip8.halted => Some("W00".to_string()),
+            Ok(()) => None,
+        }
+    }
+
+    fn register(&self, number: usize) -> Option<Vec<u8>> {
+        let chip8 = &self.chip8;
+        let bytes = match number {
+            0..=15 => vec![chip8.registers[number]],
+            REGISTER_I => chip8.index.to_be_bytes().to_vec(),
+            REGISTER_PC => chip8.pc.to_be_bytes().to_vec(),
+            REGISTER_SP => vec![chip8.sp],
+            REGISTER_DT => vec![chip8.delay_timer],
+            REGISTER_ST => vec![chip8.sound_timer],
+            _ => return None,
+        };
+
+        Some(bytes)
+    }
+
+    fn register_bytes(&self) -> Vec<u8> {
+        (0..REGISTER_COUNT)
+            .flat_map(|number| self.register(number).unwrap_or_default())
+            .collect()
+    }
+
+    fn write_register(&mut self, number: usize, bytes: &[u8]) -> Option<String> {
+        let chip8 = &mut self.chip8;
+        match (number, bytes) {
+            (0..=15, &[value]) => chip8.registers[number] = value,
+            (REGISTER_I, &[high, low]) => chip8.index = u16::from_be_bytes([high, low]),
+            (REGISTER_PC, &[high, low]) => chip8.pc = u16::from_be_bytes([high, low]),
+            (REGISTER_SP, &[value]) if value as usize <= chip8.stack.len() => chip8.sp = value,
+            (REGISTER_DT, &[value]) => chip8.delay_timer = value,
+            (REGISTER_ST, &[value]) => chip8.sound_timer = value,
+            _ => return None,
+        }
+
+        Some("OK".to_string())
+    }
+
+    fn write_registers(&mut self, bytes: &[u8]) -> Option<String> {
+        let mut offset = 0;
+        for number in 0..REGISTER_COUNT {
+            let len = self.register(number)?.len();
+            self.write_register(number, bytes.get(offset..offset + len)?)?;
+            offset += len;
+        }
+
+        Some("OK".to_string())
+    }
+
+    /// Reads the next packet, acknowledging it, or `None` once the
+    /// connection is closed.
+    fn read_packet(
+        &self,
+        reader: &mut BufReader<TcpStream>,
+        writer: &mut TcpStream,
+    ) -> io::Result<Option<String>> {
+        loop {
+            let mut byte = [0];
+            // Acks and stray interrupts are skipped until the next packet
+            loop {
+                if reader.read(&mut byte)? == 0 {
+                    return Ok(None);
+                }
+                if byte[0] == b'$' {
+                    break;
+                }
+            }
+
+            let mut data = Vec::new();
+            if reader.read_until(b'#', &mut data)? == 0 || data.pop() != Some(b'#') {
+                return Ok(None);
+            }
+            let mut checksum = [0; 2];
+            reader.read_exact(&mut checksum)?;
+
+            let valid = std::str::from_utf8(&checksum)
+                .ok()
+                .and_then(|checksum| u8::from_str_radix(checksum, 16).ok())
+                == Some(checksum_of(&data));
+            if !self.no_ack {
+                writer.write_all(if valid { b"+" } else { b"-" })?;
+            }
+            if valid {
+                return Ok(Some(String::from_utf8_lossy(&data).into_owned()));
+            }
+        }
+    }
+
+    fn send_packet(&self, writer: &mut TcpStream, data: &str) -> io::Result<()> {
+        write!(writer, "${}#{:02x}", data, checksum_of(data.as_bytes()))?;
+        writer.flush()
+    }
+}
+
+/// Checks, without blocking, whether GDB sent an interrupt. A closed
+/// connection counts as one.
+fn interrupted(reader: &mut BufReader<TcpStream>) -> io::Result<bool> {
+    if reader.buffer().is_empty() {
+        reader.get_ref().set_nonblocking(true)?;
+        let result = reader.fill_buf().map(|buffer| buffer.len());
+        reader.get_ref().set_nonblocking(false)?;
+
+        match result {
+            Ok(0) => return Ok(true),
+            Ok(_) => {}
+            Err(e) if e.kind() == ErrorKind::WouldBlock => return Ok(false),
+            Err(e) => return Err(e),
+        }
+    }
+
+    let is_interrupt = reader.buffer().first() == Some(&INTERRUPT);
+    if is_interrupt {
+        reader.consume(1);
+    }
+    Ok(is_interrupt)
+}
+
+fn stop_reply(signal: u8) -> String {
+    format!("S{:02x}", signal)
+}
+
+fn checksum_of(data: &[u8]) -> u8 {
+    data.iter().fold(0, |sum, &byte| sum.wrapping_add(byte))
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Parses `addr,len` with both in hex.
+fn parse_range(range: &str) -> Option<(usize, usize)> {
+    let (address, len) = range.split_once(',')?;
+    Some((
+        usize::from_str_radix(address, 16).ok()?,
+        usize::from_str_radix(len, 16).ok()?,
+    ))
+}
+
+fn target_xml() -> String {
+    let mut registers: Vec<String> = (0..16)
+        .map(|i| format!("<reg name=\"v{:x}\" bitsize=\"8\"/>", i))
+        .collect();
+    registers.extend([
+        "<reg name=\"i\" bitsize=\"16\" type=\"data_ptr\"/>".to_string(),
+        "<reg name=\"pc\" bitsize=\"16\" type=\"code_ptr\"/>".to_string(),
+        "<reg name=\"sp\" bitsize=\"8\"/>".to_string(),
+        "<reg name=\"dt\" bitsize=\"8\"/>".to_string(),
+        "<reg name=\"st\" bitsize=\"8\"/>".to_string(),
+    ]);
+
+    format!(
+        "<?xml version=\"1.0\"?><!DOCTYPE target SYSTEM \"gdb-target.dtd\">\
+         <target version=\"1.0\"><feature name=\"org.hachi.chip8\">{}</feature></target>",
+        registers.concat()
+    )
+}
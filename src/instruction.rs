@@ -0,0 +1,203 @@
+use std::fmt;
+
+/// A decoded CHIP-8 instruction.
+///
+/// `x` and `y` are register indices (0x0-0xF), `byte` is an 8-bit immediate
+/// and `address` a 12-bit memory address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    /// 00Cn - SCD nibble (SUPER-CHIP)
+    ScrollDown { n: u8 },
+    /// 00Dn - SCU nibble (XO-CHIP)
+    ScrollUp { n: u8 },
+    /// 00E0 - CLS
+    ClearDisplay,
+    /// 00EE - RET
+    Return,
+    /// 00FB - SCR (SUPER-CHIP)
+    ScrollRight,
+    /// 00FC - SCL (SUPER-CHIP)
+    ScrollLeft,
+    /// 00FD - EXIT (SUPER-CHIP)
+    Exit,
+    /// 00FE - LOW (SUPER-CHIP)
+    LowRes,
+    /// 00FF - HIGH (SUPER-CHIP)
+    HighRes,
+    /// 1nnn - JP addr
+    Jump { address: u16 },
+    /// 2nnn - CALL addr
+    Call { address: u16 },
+    /// 3xkk - SE Vx, byte
+    SkipEqualByte { x: u8, byte: u8 },
+    /// 4xkk - SNE Vx, byte
+    SkipNotEqualByte { x: u8, byte: u8 },
+    /// 5xy0 - SE Vx, Vy
+    SkipEqual { x: u8, y: u8 },
+    /// 5xy2 - SAVE Vx - Vy (XO-CHIP)
+    StoreRange { x: u8, y: u8 },
+    /// 5xy3 - LOAD Vx - Vy (XO-CHIP)
+    LoadRange { x: u8, y: u8 },
+    /// 6xkk - LD Vx, byte
+    LoadByte { x: u8, byte: u8 },
+    /// 7xkk - ADD Vx, byte
+    AddByte { x: u8, byte: u8 },
+    /// 8xy0 - LD Vx, Vy
+    Load { x: u8, y: u8 },
+    /// 8xy1 - OR Vx, Vy
+    Or { x: u8, y: u8 },
+    /// 8xy2 - AND Vx, Vy
+    And { x: u8, y: u8 },
+    /// 8xy3 - XOR Vx, Vy
+    Xor { x: u8, y: u8 },
+    /// 8xy4 - ADD Vx, Vy
+    Add { x: u8, y: u8 },
+    /// 8xy5 - SUB Vx, Vy
+    Sub { x: u8, y: u8 },
+    /// 8xy6 - SHR Vx {, Vy}
+    ShiftRight { x: u8, y: u8 },
+    /// 8xy7 - SUBN Vx, Vy
+    SubN { x: u8, y: u8 },
+    /// 8xyE - SHL Vx {, Vy}
+    ShiftLeft { x: u8, y: u8 },
+    /// 9xy0 - SNE Vx, Vy
+    SkipNotEqual { x: u8, y: u8 },
+    /// Annn - LD I, addr
+    LoadIndex { address: u16 },
+    /// Bnnn - JP V0, addr
+    JumpV0 { address: u16 },
+    /// Cxkk - RND Vx, byte
+    Random { x: u8, byte: u8 },
+    /// Dxyn - DRW Vx, Vy, nibble (Dxy0 draws a 16x16 sprite)
+    DrawSprite { x: u8, y: u8, n: u8 },
+    /// Ex9E - SKP Vx
+    SkipKeyPressed { x: u8 },
+    /// ExA1 - SKNP Vx
+    SkipKeyNotPressed { x: u8 },
+    /// F000 nnnn - LD I, long addr (XO-CHIP); the address is the next word in memory
+    LoadLongIndex,
+    /// Fn01 - PLANE n (XO-CHIP)
+    SelectPlanes { planes: u8 },
+    /// F002 - AUDIO (XO-CHIP)
+    LoadAudioPattern,
+    /// Fx07 - LD Vx, DT
+    LoadDelayTimer { x: u8 },
+    /// Fx0A - LD Vx, K
+    WaitKey { x: u8 },
+    /// Fx15 - LD DT, Vx
+    SetDelayTimer { x: u8 },
+    /// Fx18 - LD ST, Vx
+    SetSoundTimer { x: u8 },
+    /// Fx1E - ADD I, Vx
+    AddIndex { x: u8 },
+    /// Fx29 - LD F, Vx
+    LoadFont { x: u8 },
+    /// Fx30 - LD HF, Vx (SUPER-CHIP)
+    LoadBigFont { x: u8 },
+    /// Fx3A - PITCH Vx (XO-CHIP)
+    SetPitch { x: u8 },
+    /// Fx33 - LD B, Vx
+    StoreBcd { x: u8 },
+    /// Fx55 - LD [I], Vx
+    StoreRegisters { x: u8 },
+    /// Fx65 - LD Vx, [I]
+    LoadRegisters { x: u8 },
+    /// Fx75 - LD R, Vx (SUPER-CHIP)
+    StoreFlags { x: u8 },
+    /// Fx85 - LD Vx, R (SUPER-CHIP)
+    LoadFlags { x: u8 },
+}
+
+/// Returned by [`decode`] for opcodes that don't map to any instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeError {
+    pub opcode: u16,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown opcode 0x{:04X}", self.opcode)
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Decodes a raw two-byte opcode into an [`Instruction`].
+pub fn decode(opcode: u16) -> Result<Instruction, DecodeError> {
+    let x = ((opcode & 0x0F00) >> 8) as u8;
+    let y = ((opcode & 0x00F0) >> 4) as u8;
+    let n = (opcode & 0x000F) as u8;
+    let byte = (opcode & 0x00FF) as u8;
+    let address = opcode & 0x0FFF;
+
+    let instruction = match opcode & 0xF000 {
+        0x0000 => match opcode {
+            0x00C0..=0x00CF => Instruction::ScrollDown { n },
+            0x00D0..=0x00DF => Instruction::ScrollUp { n },
+            0x00E0 => Instruction::ClearDisplay,
+            0x00EE => Instruction::Return,
+            0x00FB => Instruction::ScrollRight,
+            0x00FC => Instruction::ScrollLeft,
+            0x00FD => Instruction::Exit,
+            0x00FE => Instruction::LowRes,
+            0x00FF => Instruction::HighRes,
+            _ => return Err(DecodeError { opcode }),
+        },
+        0x1000 => Instruction::Jump { address },
+        0x2000 => Instruction::Call { address },
+        0x3000 => Instruction::SkipEqualByte { x, byte },
+        0x4000 => Instruction::SkipNotEqualByte { x, byte },
+        0x5000 => match n {
+            0x0 => Instruction::SkipEqual { x, y },
+            0x2 => Instruction::StoreRange { x, y },
+            0x3 => Instruction::LoadRange { x, y },
+            _ => return Err(DecodeError { opcode }),
+        },
+        0x6000 => Instruction::LoadByte { x, byte },
+        0x7000 => Instruction::AddByte { x, byte },
+        0x8000 => match n {
+            0x0 => Instruction::Load { x, y },
+            0x1 => Instruction::Or { x, y },
+            0x2 => Instruction::And { x, y },
+            0x3 => Instruction::Xor { x, y },
+            0x4 => Instruction::Add { x, y },
+            0x5 => Instruction::Sub { x, y },
+            0x6 => Instruction::ShiftRight { x, y },
+            0x7 => Instruction::SubN { x, y },
+            0xE => Instruction::ShiftLeft { x, y },
+            _ => return Err(DecodeError { opcode }),
+        },
+        0x9000 if n == 0x0 => Instruction::SkipNotEqual { x, y },
+        0xA000 => Instruction::LoadIndex { address },
+        0xB000 => Instruction::JumpV0 { address },
+        0xC000 => Instruction::Random { x, byte },
+        0xD000 => Instruction::DrawSprite { x, y, n },
+        0xE000 => match byte {
+            0x9E => Instruction::SkipKeyPressed { x },
+            0xA1 => Instruction::SkipKeyNotPressed { x },
+            _ => return Err(DecodeError { opcode }),
+        },
+        0xF000 => match byte {
+            0x00 if x == 0x0 => Instruction::LoadLongIndex,
+            0x01 => Instruction::SelectPlanes { planes: x },
+            0x02 if x == 0x0 => Instruction::LoadAudioPattern,
+            0x07 => Instruction::LoadDelayTimer { x },
+            0x0A => Instruction::WaitKey { x },
+            0x15 => Instruction::SetDelayTimer { x },
+            0x18 => Instruction::SetSoundTimer { x },
+            0x1E => Instruction::AddIndex { x },
+            0x29 => Instruction::LoadFont { x },
+            0x30 => Instruction::LoadBigFont { x },
+            0x3A => Instruction::SetPitch { x },
+            0x33 => Instruction::StoreBcd { x },
+            0x55 => Instruction::StoreRegisters { x },
+            0x65 => Instruction::LoadRegisters { x },
+            0x75 => Instruction::StoreFlags { x },
+            0x85 => Instruction::LoadFlags { x },
+            _ => return Err(DecodeError { opcode }),
+        },
+        _ => return Err(DecodeError { opcode }),
+    };
+
+    Ok(instruction)
+}
@@ -1,9 +1,55 @@
+mod assembler;
+mod audio;
+mod clip;
+mod debugger;
+mod disassembler;
+mod error;
+pub mod frontend;
+mod gdb;
+mod instruction;
+mod movie;
+mod quirks;
+mod random;
+mod rewind;
+mod save_state;
+mod screenshot;
+pub mod terminal;
+
+pub use assembler::{AssembleError, assemble};
+pub use audio::{DEFAULT_BEEP_FREQUENCY, Synthesizer};
+pub use clip::{GifRecorder, PpmSequence};
+pub use debugger::{Breakpoint, Debugger};
+pub use disassembler::{Disassembly, Line, Syntax, disassemble};
+pub use error::{Chip8Error, Chip8ErrorKind};
+pub use gdb::GdbStub;
+pub use instruction::{DecodeError, Instruction, decode};
+pub use movie::Movie;
+pub use quirks::Quirks;
+pub use random::{RandomSource, Sequence, Xorshift};
+pub use rewind::Rewind;
+pub use save_state::state_slot_path;
+pub use screenshot::{ImageFormat, Palette, Screenshot};
+
+use frontend::{Audio, Display, Input};
 use log::info;
-use rand::Rng;
 use std::io::{BufReader, Error, ErrorKind, Read};
 
-const VIDEO_HEIGHT: u16 = 32;
-const VIDEO_WIDTH: u16 = 64;
+/// Timers run at 60 Hz; ~11 instructions per frame gives roughly 700
+/// instructions per second.
+pub const DEFAULT_INSTRUCTIONS_PER_FRAME: usize = 11;
+
+const MEMORY_SIZE: usize = 4096;
+const XO_CHIP_MEMORY_SIZE: usize = 0x10000;
+
+const LORES_WIDTH: u16 = 64;
+const LORES_HEIGHT: u16 = 32;
+const HIRES_WIDTH: u16 = 128;
+const HIRES_HEIGHT: u16 = 64;
+
+const FONTSET_START_ADDRESS: u16 = 0x50;
+const FONT_CHAR_SIZE: u16 = 5;
+const BIG_FONTSET_START_ADDRESS: u16 = 0xA0;
+const BIG_FONT_CHAR_SIZE: u16 = 10;
 
 const FONTSET: [u8; 80] = [
     0xF0, 0x90, 0x90, 0x90, 0xF0, // 0
@@ -24,23 +70,93 @@ const FONTSET: [u8; 80] = [
     0xF0, 0x80, 0xF0, 0x80, 0x80, // F
 ];
 
+// SUPER-CHIP 8x10 font (A-F added by XO-CHIP)
+const BIG_FONTSET: [u8; 160] = [
+    0xFF, 0xFF, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xFF, 0xFF, // 0
+    0x18, 0x78, 0x78, 0x18, 0x18, 0x18, 0x18, 0x18, 0xFF, 0xFF, // 1
+    0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, // 2
+    0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF, // 3
+    0xC3, 0xC3, 0xC3, 0xC3, 0xFF, 0xFF, 0x03, 0x03, 0x03, 0x03, // 4
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF, // 5
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC3, 0xC3, 0xFF, 0xFF, // 6
+    0xFF, 0xFF, 0x03, 0x03, 0x06, 0x0C, 0x18, 0x18, 0x18, 0x18, // 7
+    0xFF, 0xFF, 0xC3, 0xC3, 0xFF, 0xFF, 0xC3, 0xC3, 0xFF, 0xFF, // 8
+    0xFF, 0xFF, 0xC3, 0xC3, 0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF, // 9
+    0x7E, 0xFF, 0xC3, 0xC3, 0xC3, 0xFF, 0xFF, 0xC3, 0xC3, 0xC3, // A
+    0xFC, 0xFC, 0xC3, 0xC3, 0xFC, 0xFC, 0xC3, 0xC3, 0xFC, 0xFC, // B
+    0x3C, 0xFF, 0xC3, 0xC0, 0xC0, 0xC0, 0xC0, 0xC3, 0xFF, 0x3C, // C
+    0xFC, 0xFE, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xFE, 0xFC, // D
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, // E
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC0, 0xC0, 0xC0, 0xC0, // F
+];
+
+/// Progress of an `Fx0A` (wait for key) instruction.
+///
+/// Like the COSMAC VIP, the interpreter halts until a key is pressed *and*
+/// released; the released key is then stored in Vx.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyWait {
+    Idle,
+    Waiting { vx: u8 },
+    Pressed { vx: u8, key: u8 },
+}
+
+/// A CHIP-8 machine.
+///
+/// Machines are `Clone`, `Send` and `Sync`, so they can be forked with
+/// [`Chip8::fork`] and run on many threads in parallel.
+#[derive(Clone)]
 pub struct Chip8 {
     pub registers: [u8; 16],
-    pub memory: [u8; 4096],
+    /// 4 KiB by default, 64 KiB for XO-CHIP.
+    pub memory: Vec<u8>,
     pub index: u16,
     pub pc: u16,
     pub stack: [u16; 16],
     pub sp: u8,
     pub keypad: [bool; 16],
-    pub video: [bool; 64 * 32],
+    pub key_wait: KeyWait,
+    /// Display buffer sized for hi-res mode. Pixels are stored row by row
+    /// using the width of the current resolution; each pixel holds one bit
+    /// per XO-CHIP bitplane (bit 0 for plane 1, bit 1 for plane 2).
+    pub video: [u8; 128 * 64],
+    /// Bitplanes affected by drawing, clearing and scrolling, selected by Fn01.
+    pub planes: u8,
+    /// SUPER-CHIP 128x64 mode, toggled by 00FF/00FE.
+    pub hires: bool,
+    pub delay_timer: u8,
+    pub sound_timer: u8,
+    /// Set after a draw when `quirks.display_wait` is on; cleared on the next timer tick.
+    pub waiting_for_vblank: bool,
+    /// Set by 00FD; the interpreter stops executing instructions.
+    pub halted: bool,
+    /// SUPER-CHIP RPL user flags, saved and restored by Fx75/Fx85.
+    pub rpl_flags: [u8; 16],
+    /// XO-CHIP 1-bit audio pattern (128 samples), loaded by F002.
+    pub audio_pattern: [u8; 16],
+    /// XO-CHIP playback rate of the audio pattern, set by Fx3A.
+    pub pitch: u8,
+    /// Set when the display changes, until the next
+    /// [`Display`](frontend::Display) notification.
+    pub display_dirty: bool,
+    /// Set when the audio pattern or pitch changes, until the next
+    /// [`Audio`](frontend::Audio) notification.
+    pub audio_dirty: bool,
+    /// Instructions executed by [`Chip8::step`] since the last timer tick.
+    pub frame_cycles: usize,
+    pub quirks: Quirks,
     pub opcode: u16,
-    pub rand_fn: Box<dyn Fn() -> u8>,
+    /// Source of `Cxkk`'s random bytes, seeded from entropy by default.
+    pub random: Box<dyn RandomSource>,
 }
 
 impl Default for Chip8 {
     fn default() -> Self {
-        let mut memory = [0; 4096];
-        memory[0x50..0x50 + FONTSET.len()].copy_from_slice(&FONTSET);
+        let mut memory = vec![0; MEMORY_SIZE];
+        let font_start = FONTSET_START_ADDRESS as usize;
+        memory[font_start..font_start + FONTSET.len()].copy_from_slice(&FONTSET);
+        let big_font_start = BIG_FONTSET_START_ADDRESS as usize;
+        memory[big_font_start..big_font_start + BIG_FONTSET.len()].copy_from_slice(&BIG_FONTSET);
 
         Self {
             registers: [0; 16],
@@ -50,14 +166,49 @@ impl Default for Chip8 {
             stack: [0; 16],
             sp: 0,
             keypad: [false; 16],
-            video: [false; 64 * 32],
+            key_wait: KeyWait::Idle,
+            video: [0; 128 * 64],
+            planes: 1,
+            hires: false,
+            delay_timer: 0,
+            sound_timer: 0,
+            waiting_for_vblank: false,
+            halted: false,
+            rpl_flags: [0; 16],
+            audio_pattern: [0; 16],
+            pitch: 64,
+            display_dirty: true,
+            audio_dirty: true,
+            frame_cycles: 0,
+            quirks: Quirks::default(),
             opcode: 0,
-            rand_fn: Box::new(Self::default_rand_gen),
+            random: Box::new(Xorshift::from_entropy()),
         }
     }
 }
 
 impl Chip8 {
+    pub fn new(quirks: Quirks) -> Self {
+        Self {
+            quirks,
+            ..Default::default()
+        }
+    }
+
+    /// An XO-CHIP machine: XO-CHIP quirks and 64 KiB of memory.
+    pub fn xo_chip() -> Self {
+        let mut chip8 = Self::new(Quirks::xo_chip());
+        chip8.memory.resize(XO_CHIP_MEMORY_SIZE, 0);
+        chip8
+    }
+
+    /// An independent copy of the machine, including its random number
+    /// generator: both continue identically given the same input. Reseed the
+    /// fork with [`Chip8::seed_rng`] to make them diverge, e.g. when fuzzing.
+    pub fn fork(&self) -> Self {
+        self.clone()
+    }
+
     pub fn load_rom_from_reader<R: Read>(&mut self, reader: R) -> Result<(), Error> {
         let buf_reader = BufReader::new(reader);
 
@@ -73,104 +224,419 @@ impl Chip8 {
         Ok(())
     }
 
-    // Default random number generator
-    fn default_rand_gen() -> u8 {
-        let mut rng = rand::rng();
-        rng.random_range(0..=255)
+    /// Fetches the opcode at `pc`, advances `pc` past it and executes it.
+    ///
+    /// Does nothing once halted, while an `Fx0A` instruction is waiting for a
+    /// key or while a draw is waiting for the vertical blank. On error `pc` is
+    /// left pointing at the faulting instruction.
+    pub fn cycle(&mut self) -> Result<(), Chip8Error> {
+        if self.halted || self.is_waiting_for_key() || self.waiting_for_vblank {
+            return Ok(());
+        }
+
+        let pc = self.pc;
+        let result = self.fetch().and_then(|()| {
+            self.pc = self.pc.wrapping_add(2);
+            self.execute(decode(self.opcode)?)
+        });
+
+        result.map_err(|kind| {
+            self.pc = pc;
+            Chip8Error {
+                kind,
+                pc,
+                opcode: self.opcode,
+            }
+        })
+    }
+
+    fn fetch(&mut self) -> Result<(), Chip8ErrorKind> {
+        let pc = self.pc as usize;
+        self.opcode = 0;
+        self.check_memory_range(pc, 2)?;
+
+        self.opcode = u16::from_be_bytes([self.memory[pc], self.memory[pc + 1]]);
+        Ok(())
+    }
+
+    /// Executes a decoded instruction against the current state.
+    pub fn execute(&mut self, instruction: Instruction) -> Result<(), Chip8ErrorKind> {
+        match instruction {
+            Instruction::ScrollDown { n } => self.scroll_down(n),
+            Instruction::ScrollUp { n } => self.scroll_up(n),
+            Instruction::ClearDisplay => self.clear_display(),
+            Instruction::Return => self.ret()?,
+            Instruction::ScrollRight => self.scroll_right(),
+            Instruction::ScrollLeft => self.scroll_left(),
+            Instruction::Exit => self.exit(),
+            Instruction::LowRes => self.set_hires(false),
+            Instruction::HighRes => self.set_hires(true),
+            Instruction::Jump { address } => self.jump(address),
+            Instruction::Call { address } => self.call(address)?,
+            Instruction::SkipEqualByte { x, byte } => self.skip_equal_vx_byte(x, byte),
+            Instruction::SkipNotEqualByte { x, byte } => self.skip_not_equal_vx_byte(x, byte),
+            Instruction::SkipEqual { x, y } => self.skip_equal_vx_vy(x, y),
+            Instruction::StoreRange { x, y } => self.store_range(x, y)?,
+            Instruction::LoadRange { x, y } => self.load_range(x, y)?,
+            Instruction::LoadByte { x, byte } => self.load_vx_byte(x, byte),
+            Instruction::AddByte { x, byte } => self.add_vx_byte(x, byte),
+            Instruction::Load { x, y } => self.load_vx_vy(x, y),
+            Instruction::Or { x, y } => self.or_vx_vy(x, y),
+            Instruction::And { x, y } => self.and_vx_vy(x, y),
+            Instruction::Xor { x, y } => self.xor_vx_vy(x, y),
+            Instruction::Add { x, y } => self.add_vx_vy(x, y),
+            Instruction::Sub { x, y } => self.sub_vx_vy(x, y),
+            Instruction::ShiftRight { x, y } => self.shr_vx(x, y),
+            Instruction::SubN { x, y } => self.subn_vx_vy(x, y),
+            Instruction::ShiftLeft { x, y } => self.shl_vx(x, y),
+            Instruction::SkipNotEqual { x, y } => self.skip_not_equal_vx_vy(x, y),
+            Instruction::LoadIndex { address } => self.ld_index(address),
+            Instruction::JumpV0 { address } => self.jump_v0(address)?,
+            Instruction::Random { x, byte } => self.rnd_vx_byte(x, byte),
+            Instruction::DrawSprite { x, y, n } => self.draw_vx_vy_n(x, y, n)?,
+            Instruction::SkipKeyPressed { x } => self.skip_key_pressed_vx(x),
+            Instruction::SkipKeyNotPressed { x } => self.skip_key_not_pressed_vx(x),
+            Instruction::LoadLongIndex => self.load_long_index()?,
+            Instruction::SelectPlanes { planes } => self.select_planes(planes),
+            Instruction::LoadAudioPattern => self.load_audio_pattern()?,
+            Instruction::LoadDelayTimer { x } => self.load_vx_delay_timer(x),
+            Instruction::WaitKey { x } => self.load_vx_key(x),
+            Instruction::SetDelayTimer { x } => self.load_delay_timer_vx(x),
+            Instruction::SetSoundTimer { x } => self.load_sound_timer_vx(x),
+            Instruction::AddIndex { x } => self.add_index_vx(x),
+            Instruction::LoadFont { x } => self.load_font_vx(x),
+            Instruction::LoadBigFont { x } => self.load_big_font_vx(x),
+            Instruction::SetPitch { x } => self.load_pitch_vx(x),
+            Instruction::StoreBcd { x } => self.load_bcd_vx(x)?,
+            Instruction::StoreRegisters { x } => self.store_registers(x)?,
+            Instruction::LoadRegisters { x } => self.load_registers(x)?,
+            Instruction::StoreFlags { x } => self.store_flags(x),
+            Instruction::LoadFlags { x } => self.load_flags(x),
+        }
+
+        Ok(())
+    }
+
+    /// Skips the next instruction, which is 4 bytes long if it's XO-CHIP's
+    /// F000 NNNN.
+    fn skip_next_instruction(&mut self) {
+        let pc = self.pc as usize;
+        let is_long_instruction = self.memory.get(pc..pc + 2) == Some(&[0xF0, 0x00]);
+
+        self.pc = self
+            .pc
+            .wrapping_add(if is_long_instruction { 4 } else { 2 });
+    }
+
+    /// Checks that `len` bytes starting at `address` lie within memory.
+    fn check_memory_range(&self, address: usize, len: usize) -> Result<(), Chip8ErrorKind> {
+        if address + len > self.memory.len() {
+            return Err(Chip8ErrorKind::MemoryOutOfBounds {
+                address: address.max(self.memory.len()),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Decrements the delay and sound timers and ends the wait for the
+    /// vertical blank. Should be called at 60 Hz.
+    pub fn tick_timers(&mut self) {
+        self.frame_cycles = 0;
+        self.waiting_for_vblank = false;
+        self.delay_timer = self.delay_timer.saturating_sub(1);
+        self.sound_timer = self.sound_timer.saturating_sub(1);
+    }
+
+    /// Runs one 60 Hz frame: `instructions_per_frame` cycles followed by a
+    /// timer tick.
+    pub fn run_frame(&mut self, instructions_per_frame: usize) -> Result<(), Chip8Error> {
+        for _ in 0..instructions_per_frame {
+            self.cycle()?;
+        }
+
+        self.tick_timers();
+        Ok(())
+    }
+
+    /// Executes one instruction like [`Chip8::cycle`], ticking the timers
+    /// after every `instructions_per_frame` instructions: [`Chip8::run_frame`]
+    /// one instruction at a time, for debuggers.
+    pub fn step(&mut self, instructions_per_frame: usize) -> Result<(), Chip8Error> {
+        let result = self.cycle();
+        self.frame_cycles += 1;
+        if self.frame_cycles >= instructions_per_frame.max(1) {
+            self.tick_timers();
+        }
+
+        result
+    }
+
+    /// Runs one frame like [`Chip8::run_frame`], driving frontend backends:
+    /// the keypad is first updated from `input`, then `audio` and `display`
+    /// are notified of what changed. The display is presented even if an
+    /// instruction fails.
+    pub fn run_frame_with(
+        &mut self,
+        instructions_per_frame: usize,
+        display: &mut (impl Display + ?Sized),
+        input: &mut (impl Input + ?Sized),
+        audio: &mut (impl Audio + ?Sized),
+    ) -> Result<(), Chip8Error> {
+        if let Some(keypad) = input.keypad() {
+            self.set_keypad(keypad);
+        }
+        let was_playing = self.sound_timer > 0;
+
+        let result = (0..instructions_per_frame).try_for_each(|_| self.cycle());
+
+        if self.audio_dirty {
+            audio.set_pattern(&self.audio_pattern, self.pitch);
+            self.audio_dirty = false;
+        }
+        // Reported before the timer tick so that one frame beeps are heard
+        let playing = self.sound_timer > 0;
+        if playing != was_playing {
+            audio.set_playing(playing);
+        }
+        if result.is_ok() {
+            self.tick_timers();
+            if playing && self.sound_timer == 0 {
+                audio.set_playing(false);
+            }
+        }
+
+        if self.display_dirty {
+            display.present(self);
+            self.display_dirty = false;
+        }
+
+        result
+    }
+
+    /// Width in pixels of the current resolution.
+    pub fn video_width(&self) -> usize {
+        if self.hires { HIRES_WIDTH } else { LORES_WIDTH }.into()
+    }
+
+    /// Height in pixels of the current resolution.
+    pub fn video_height(&self) -> usize {
+        if self.hires {
+            HIRES_HEIGHT
+        } else {
+            LORES_HEIGHT
+        }
+        .into()
+    }
+
+    /// Renders the visible display as text, one line per row: `.` for unlit
+    /// pixels, `#` for plane 1, `+` for plane 2 and `@` for both.
+    pub fn video_ascii(&self) -> String {
+        let width = self.video_width();
+        let mut ascii = String::with_capacity((width + 1) * self.video_height());
+
+        for row in self.video[..width * self.video_height()].chunks(width) {
+            ascii.extend(
+                row.iter()
+                    .map(|&pixel| ['.', '#', '+', '@'][pixel as usize & 0b11]),
+            );
+            ascii.push('\n');
+        }
+
+        ascii
+    }
+
+    /// 64-bit FNV-1a hash of the resolution and visible pixels, for comparing
+    /// framebuffers without storing them.
+    pub fn video_hash(&self) -> u64 {
+        let (width, height) = (self.video_width(), self.video_height());
+        let header = [width as u8, height as u8];
+
+        fnv1a(header.iter().chain(&self.video[..width * height]))
+    }
+
+    pub fn is_waiting_for_key(&self) -> bool {
+        self.key_wait != KeyWait::Idle
+    }
+
+    /// Presses and releases keys so that exactly the keys set in `keypad`
+    /// are held down.
+    pub fn set_keypad(&mut self, keypad: [bool; 16]) {
+        for (key, held) in keypad.into_iter().enumerate() {
+            if held && !self.keypad[key] {
+                self.press_key(key as u8);
+            } else if !held && self.keypad[key] {
+                self.release_key(key as u8);
+            }
+        }
     }
 
+    /// Marks `key` (0x0-0xF) as held down.
+    pub fn press_key(&mut self, key: u8) {
+        self.keypad[key as usize] = true;
+
+        if let KeyWait::Waiting { vx } = self.key_wait {
+            self.key_wait = KeyWait::Pressed { vx, key };
+        }
+    }
+
+    /// Marks `key` (0x0-0xF) as released, completing a pending `Fx0A` if it
+    /// was the key pressed while waiting.
+    pub fn release_key(&mut self, key: u8) {
+        self.keypad[key as usize] = false;
+
+        if let KeyWait::Pressed { vx, key: pressed } = self.key_wait
+            && pressed == key
+        {
+            self.registers[vx as usize] = key;
+            self.key_wait = KeyWait::Idle;
+        }
+    }
+
+    /// Replaces the random number generator with one seeded with `seed`, so
+    /// that runs with the same seed and input are identical.
+    pub fn seed_rng(&mut self, seed: u64) {
+        self.random = Box::new(Xorshift::new(seed));
+    }
+
+    /// Clears the selected bitplanes.
     pub fn clear_display(&mut self) {
-        self.video = [false; 64 * 32];
+        let planes = self.planes;
+
+        self.video.iter_mut().for_each(|pixel| *pixel &= !planes);
+        self.display_dirty = true;
+    }
+
+    pub fn scroll_down(&mut self, n: u8) {
+        self.scroll(0, n as isize);
+    }
+
+    pub fn scroll_up(&mut self, n: u8) {
+        self.scroll(0, -(n as isize));
+    }
+
+    pub fn scroll_right(&mut self) {
+        self.scroll(4, 0);
     }
 
-    pub fn ret(&mut self) {
+    pub fn scroll_left(&mut self) {
+        self.scroll(-4, 0);
+    }
+
+    /// Moves the selected bitplanes by (dx, dy) pixels, filling the
+    /// uncovered area with blank pixels.
+    fn scroll(&mut self, dx: isize, dy: isize) {
+        let (width, height) = (self.video_width() as isize, self.video_height() as isize);
+        let planes = self.planes;
+        let previous = self.video;
+
+        for y in 0..height {
+            for x in 0..width {
+                let (src_x, src_y) = (x - dx, y - dy);
+                let moved = if (0..width).contains(&src_x) && (0..height).contains(&src_y) {
+                    previous[(src_y * width + src_x) as usize] & planes
+                } else {
+                    0
+                };
+
+                let pos = (y * width + x) as usize;
+                self.video[pos] = (previous[pos] & !planes) | moved;
+            }
+        }
+        self.display_dirty = true;
+    }
+
+    pub fn exit(&mut self) {
+        self.halted = true;
+    }
+
+    /// Switches between 64x32 and 128x64 resolution, clearing every bitplane.
+    pub fn set_hires(&mut self, hires: bool) {
+        self.hires = hires;
+        self.video = [0; 128 * 64];
+        self.display_dirty = true;
+    }
+
+    pub fn ret(&mut self) -> Result<(), Chip8ErrorKind> {
+        if self.sp == 0 {
+            return Err(Chip8ErrorKind::StackUnderflow);
+        }
+
         self.sp -= 1;
         self.pc = self.stack[self.sp as usize];
+        Ok(())
     }
 
-    pub fn jump(&mut self) {
-        self.pc = self.opcode & 0x0FFFu16;
+    pub fn jump(&mut self, address: u16) {
+        self.pc = address;
     }
 
-    pub fn call(&mut self) {
+    pub fn call(&mut self, address: u16) -> Result<(), Chip8ErrorKind> {
+        if self.sp as usize >= self.stack.len() {
+            return Err(Chip8ErrorKind::StackOverflow);
+        }
+
         self.stack[self.sp as usize] = self.pc;
         self.sp += 1;
-        self.pc = self.opcode & 0x0FFFu16;
+        self.pc = address;
+        Ok(())
     }
 
-    pub fn skip_equal_vx_byte(&mut self) {
-        let vx = (self.opcode & 0x0F00) >> 8;
-        let byte = (self.opcode & 0x00FFu16) as u8;
-
+    pub fn skip_equal_vx_byte(&mut self, vx: u8, byte: u8) {
         if self.registers[vx as usize] == byte {
-            self.pc += 2;
+            self.skip_next_instruction();
         }
     }
 
-    pub fn skip_not_equal_vx_byte(&mut self) {
-        let vx = (self.opcode & 0x0F00) >> 8;
-        let byte = (self.opcode & 0x00FFu16) as u8;
-
+    pub fn skip_not_equal_vx_byte(&mut self, vx: u8, byte: u8) {
         if self.registers[vx as usize] != byte {
-            self.pc += 2;
+            self.skip_next_instruction();
         }
     }
 
-    pub fn skip_equal_vx_vy(&mut self) {
-        let vx = (self.opcode & 0x0F00) >> 8;
-        let vy = (self.opcode & 0x00F0) >> 4;
-
+    pub fn skip_equal_vx_vy(&mut self, vx: u8, vy: u8) {
         if self.registers[vx as usize] == self.registers[vy as usize] {
-            self.pc += 2;
+            self.skip_next_instruction();
         }
     }
 
-    pub fn load_vx_byte(&mut self) {
-        let vx = (self.opcode & 0x0F00) >> 8;
-        let byte = (self.opcode & 0x00FFu16) as u8;
-
+    pub fn load_vx_byte(&mut self, vx: u8, byte: u8) {
         self.registers[vx as usize] = byte;
     }
 
-    pub fn load_vx_vy(&mut self) {
-        let vx = (self.opcode & 0x0F00) >> 8;
-        let vy = (self.opcode & 0x00F0) >> 4;
-
+    pub fn load_vx_vy(&mut self, vx: u8, vy: u8) {
         self.registers[vx as usize] = self.registers[vy as usize];
     }
 
-    pub fn add_vx_byte(&mut self) {
-        let vx = (self.opcode & 0x0F00) >> 8;
-        let byte = (self.opcode & 0x00FFu16) as u8;
-
+    pub fn add_vx_byte(&mut self, vx: u8, byte: u8) {
         self.registers[vx as usize] = self.registers[vx as usize].wrapping_add(byte);
     }
 
-    pub fn or_vx_vy(&mut self) {
-        let vx = (self.opcode & 0x0F00u16) >> 8;
-        let vy = (self.opcode & 0x00F0u16) >> 4;
-
+    pub fn or_vx_vy(&mut self, vx: u8, vy: u8) {
         self.registers[vx as usize] |= self.registers[vy as usize];
-    }
 
-    pub fn and_vx_vy(&mut self) {
-        let vx = (self.opcode & 0x0F00u16) >> 8;
-        let vy = (self.opcode & 0x00F0u16) >> 4;
+        if self.quirks.vf_reset {
+            self.registers[0xF] = 0;
+        }
+    }
 
+    pub fn and_vx_vy(&mut self, vx: u8, vy: u8) {
         self.registers[vx as usize] &= self.registers[vy as usize];
-    }
 
-    pub fn xor_vx_vy(&mut self) {
-        let vx = (self.opcode & 0x0F00u16) >> 8;
-        let vy = (self.opcode & 0x00F0u16) >> 4;
+        if self.quirks.vf_reset {
+            self.registers[0xF] = 0;
+        }
+    }
 
+    pub fn xor_vx_vy(&mut self, vx: u8, vy: u8) {
         self.registers[vx as usize] ^= self.registers[vy as usize];
-    }
 
-    pub fn add_vx_vy(&mut self) {
-        let vx = (self.opcode & 0x0F00u16) >> 8;
-        let vy = (self.opcode & 0x00F0u16) >> 4;
+        if self.quirks.vf_reset {
+            self.registers[0xF] = 0;
+        }
+    }
 
+    pub fn add_vx_vy(&mut self, vx: u8, vy: u8) {
         let (result, has_overflow) =
             self.registers[vx as usize].overflowing_add(self.registers[vy as usize]);
 
@@ -178,10 +644,7 @@ impl Chip8 {
         self.registers[0xF] = has_overflow as u8;
     }
 
-    pub fn sub_vx_vy(&mut self) {
-        let vx = (self.opcode & 0x0F00u16) >> 8;
-        let vy = (self.opcode & 0x00F0u16) >> 4;
-
+    pub fn sub_vx_vy(&mut self, vx: u8, vy: u8) {
         let (result, has_borrow) =
             self.registers[vx as usize].overflowing_sub(self.registers[vy as usize]);
 
@@ -189,18 +652,16 @@ impl Chip8 {
         self.registers[0xF] = !has_borrow as u8;
     }
 
-    pub fn shr_vx(&mut self) {
-        let vx = (self.opcode & 0x0F00u16) >> 8;
-        let lsb = self.registers[vx as usize] & 0x1u8;
+    pub fn shr_vx(&mut self, vx: u8, vy: u8) {
+        let source = if self.quirks.shift_uses_vy { vy } else { vx };
+        let lsb = self.registers[source as usize] & 0x1u8;
 
+        // VF is written last so that the flag wins when VF is the target
+        self.registers[vx as usize] = self.registers[source as usize] >> 1;
         self.registers[0xF] = lsb;
-        self.registers[vx as usize] >>= 1;
     }
 
-    pub fn subn_vx_vy(&mut self) {
-        let vx = (self.opcode & 0x0F00u16) >> 8;
-        let vy = (self.opcode & 0x00F0u16) >> 4;
-
+    pub fn subn_vx_vy(&mut self, vx: u8, vy: u8) {
         let (result, has_borrow) =
             self.registers[vy as usize].overflowing_sub(self.registers[vx as usize]);
 
@@ -208,74 +669,284 @@ impl Chip8 {
         self.registers[0xF] = !has_borrow as u8;
     }
 
-    pub fn shl_vx(&mut self) {
-        let vx = (self.opcode & 0x0F00u16) >> 8;
+    pub fn shl_vx(&mut self, vx: u8, vy: u8) {
+        let source = if self.quirks.shift_uses_vy { vy } else { vx };
+        let msb = self.registers[source as usize] >> 7;
 
-        let (result, has_overflow) = self.registers[vx as usize].overflowing_mul(2);
+        self.registers[vx as usize] = self.registers[source as usize] << 1;
+        self.registers[0xF] = msb;
+    }
 
-        self.registers[0xF] = has_overflow as u8;
-        self.registers[vx as usize] = result;
+    pub fn skip_not_equal_vx_vy(&mut self, vx: u8, vy: u8) {
+        if self.registers[vx as usize] != self.registers[vy as usize] {
+            self.skip_next_instruction();
+        }
     }
 
-    pub fn skip_not_equal_vx_vy(&mut self) {
-        let vx = (self.opcode & 0x0F00u16) >> 8;
-        let vy = (self.opcode & 0x00F0u16) >> 4;
+    pub fn ld_index(&mut self, address: u16) {
+        self.index = address;
+    }
 
-        if self.registers[vx as usize] != self.registers[vy as usize] {
-            self.pc += 2;
+    pub fn jump_v0(&mut self, address: u16) -> Result<(), Chip8ErrorKind> {
+        let register = if self.quirks.jump_uses_vx {
+            (address & 0x0F00) >> 8
+        } else {
+            0
+        };
+        let target = self.registers[register as usize] as u16 + address;
+        self.check_memory_range(target as usize, 2)?;
+
+        self.pc = target;
+        Ok(())
+    }
+
+    pub fn rnd_vx_byte(&mut self, vx: u8, byte: u8) {
+        self.registers[vx as usize] = self.random.next_byte() & byte;
+    }
+
+    /// Draws an 8xN sprite, or a 16x16 sprite when `height` is 0, on each
+    /// selected bitplane. With both planes selected, the sprite data for
+    /// plane 2 follows the data for plane 1.
+    pub fn draw_vx_vy_n(&mut self, vx: u8, vy: u8, height: u8) -> Result<(), Chip8ErrorKind> {
+        let (sprite_width, sprite_height) = match height {
+            0 if self.quirks.dxy0_draws_nothing => (8, 0),
+            0 => (16, 16),
+            _ => (8, height as usize),
+        };
+        let bytes_per_row = sprite_width / 8;
+        let sprite_size = bytes_per_row * sprite_height;
+        let plane_count = (self.planes & 0b11).count_ones() as usize;
+        self.check_memory_range(self.index as usize, sprite_size * plane_count)?;
+
+        let (width, height) = (self.video_width(), self.video_height());
+
+        // The starting position always wraps around the screen
+        let x_pos = self.registers[vx as usize] as usize % width;
+        let y_pos = self.registers[vy as usize] as usize % height;
+
+        self.registers[0xF] = 0;
+
+        let mut sprite_address = self.index as usize;
+        for plane in [0b01, 0b10] {
+            if self.planes & plane == 0 {
+                continue;
+            }
+
+            for row in 0..sprite_height {
+                let row_address = sprite_address + row * bytes_per_row;
+                let sprite_row = self.memory[row_address..row_address + bytes_per_row]
+                    .iter()
+                    .fold(0u16, |bits, &byte| (bits << 8) | byte as u16);
+
+                for col in 0..sprite_width {
+                    let sprite_pixel = (sprite_row >> (sprite_width - 1 - col)) & 1 != 0;
+
+                    // Pixels past the edges are either clipped or wrapped
+                    let (mut screen_x, mut screen_y) = (x_pos + col, y_pos + row);
+                    if self.quirks.clip_sprites {
+                        if screen_x >= width || screen_y >= height {
+                            continue;
+                        }
+                    } else {
+                        screen_x %= width;
+                        screen_y %= height;
+                    }
+                    let buffer_pos = screen_y * width + screen_x;
+
+                    let screen_pixel = self.video[buffer_pos] & plane != 0;
+
+                    // Check for collision (when sprite pixel is on and screen pixel is on)
+                    if sprite_pixel && screen_pixel {
+                        self.registers[0xF] = 1;
+                    }
+
+                    // XOR the pixels
+                    if sprite_pixel {
+                        self.video[buffer_pos] ^= plane;
+                    }
+                }
+            }
+
+            sprite_address += sprite_size;
         }
+        self.display_dirty = true;
+
+        if self.quirks.display_wait {
+            self.waiting_for_vblank = true;
+        }
+
+        Ok(())
     }
 
-    pub fn ld_index(&mut self) {
-        let address = self.opcode & 0x0FFF;
+    pub fn load_vx_delay_timer(&mut self, vx: u8) {
+        self.registers[vx as usize] = self.delay_timer;
+    }
 
-        self.index = address;
+    pub fn load_vx_key(&mut self, vx: u8) {
+        self.key_wait = KeyWait::Waiting { vx };
     }
 
-    pub fn jump_v0(&mut self) {
-        let address = self.opcode & 0x0FFF;
+    pub fn load_delay_timer_vx(&mut self, vx: u8) {
+        self.delay_timer = self.registers[vx as usize];
+    }
 
-        self.pc = self.registers[0] as u16 + address;
+    pub fn load_sound_timer_vx(&mut self, vx: u8) {
+        self.sound_timer = self.registers[vx as usize];
     }
 
-    pub fn rnd_vx_byte(&mut self) {
-        let vx = (self.opcode & 0x0F00u16) >> 8;
-        let byte = (self.opcode & 0x00FF) as u8;
+    pub fn add_index_vx(&mut self, vx: u8) {
+        self.index = self.index.wrapping_add(self.registers[vx as usize] as u16);
+    }
+
+    pub fn load_font_vx(&mut self, vx: u8) {
+        let digit = (self.registers[vx as usize] & 0x0F) as u16;
 
-        self.registers[vx as usize] = (self.rand_fn)() & byte;
+        self.index = FONTSET_START_ADDRESS + digit * FONT_CHAR_SIZE;
     }
 
-    pub fn draw_vx_vy_n(&mut self) {
-        let vx = (self.opcode & 0x0F00u16) >> 8;
-        let vy = (self.opcode & 0x00F0u16) >> 4;
-        let height = self.opcode & 0x000Fu16;
+    pub fn load_big_font_vx(&mut self, vx: u8) {
+        let digit = (self.registers[vx as usize] & 0x0F) as u16;
 
-        let x_pos = self.registers[vx as usize] as u16;
-        let y_pos = self.registers[vy as usize] as u16;
+        self.index = BIG_FONTSET_START_ADDRESS + digit * BIG_FONT_CHAR_SIZE;
+    }
 
-        self.registers[0xF] = 0;
+    pub fn load_bcd_vx(&mut self, vx: u8) -> Result<(), Chip8ErrorKind> {
+        let value = self.registers[vx as usize];
+        let index = self.index as usize;
+        self.check_memory_range(index, 3)?;
 
-        for row in 0..height {
-            let sprite_byte = self.memory[(self.index + row) as usize];
-
-            for col in 0..8u16 {
-                let sprite_pixel = (sprite_byte & (0x80 >> col)) != 0;
-                
-                // Handle screen wrapping
-                let screen_x = (x_pos + col) % VIDEO_WIDTH;
-                let screen_y = (y_pos + row) % VIDEO_HEIGHT;
-                let buffer_pos = (screen_y * VIDEO_WIDTH + screen_x) as usize;
-                
-                let screen_pixel = self.video[buffer_pos];
-
-                // Check for collision (when sprite pixel is on and screen pixel is on)
-                if sprite_pixel && screen_pixel {
-                    self.registers[0xF] = 1;
-                }
+        self.memory[index] = value / 100;
+        self.memory[index + 1] = (value / 10) % 10;
+        self.memory[index + 2] = value % 10;
+        Ok(())
+    }
 
-                // XOR the pixels
-                self.video[buffer_pos] = screen_pixel ^ sprite_pixel;
-            }
+    pub fn store_registers(&mut self, vx: u8) -> Result<(), Chip8ErrorKind> {
+        let vx = vx as usize;
+        let index = self.index as usize;
+        self.check_memory_range(index, vx + 1)?;
+
+        self.memory[index..=index + vx].copy_from_slice(&self.registers[..=vx]);
+
+        if self.quirks.memory_increments_index {
+            self.index = self.index.wrapping_add(vx as u16 + 1);
+        }
+
+        Ok(())
+    }
+
+    pub fn load_registers(&mut self, vx: u8) -> Result<(), Chip8ErrorKind> {
+        let vx = vx as usize;
+        let index = self.index as usize;
+        self.check_memory_range(index, vx + 1)?;
+
+        self.registers[..=vx].copy_from_slice(&self.memory[index..=index + vx]);
+
+        if self.quirks.memory_increments_index {
+            self.index = self.index.wrapping_add(vx as u16 + 1);
+        }
+
+        Ok(())
+    }
+
+    pub fn skip_key_pressed_vx(&mut self, vx: u8) {
+        let key = self.registers[vx as usize] & 0x0F;
+
+        if self.keypad[key as usize] {
+            self.skip_next_instruction();
+        }
+    }
+
+    pub fn skip_key_not_pressed_vx(&mut self, vx: u8) {
+        let key = self.registers[vx as usize] & 0x0F;
+
+        if !self.keypad[key as usize] {
+            self.skip_next_instruction();
+        }
+    }
+
+    pub fn store_flags(&mut self, vx: u8) {
+        let vx = vx as usize;
+
+        self.rpl_flags[..=vx].copy_from_slice(&self.registers[..=vx]);
+    }
+
+    pub fn load_flags(&mut self, vx: u8) {
+        let vx = vx as usize;
+
+        self.registers[..=vx].copy_from_slice(&self.rpl_flags[..=vx]);
+    }
+
+    /// Registers Vx to Vy, in that order (which may be descending).
+    fn register_range(vx: u8, vy: u8) -> Vec<usize> {
+        if vx <= vy {
+            (vx as usize..=vy as usize).collect()
+        } else {
+            (vy as usize..=vx as usize).rev().collect()
         }
     }
+
+    pub fn store_range(&mut self, vx: u8, vy: u8) -> Result<(), Chip8ErrorKind> {
+        let registers = Self::register_range(vx, vy);
+        let index = self.index as usize;
+        self.check_memory_range(index, registers.len())?;
+
+        for (offset, register) in registers.into_iter().enumerate() {
+            self.memory[index + offset] = self.registers[register];
+        }
+
+        Ok(())
+    }
+
+    pub fn load_range(&mut self, vx: u8, vy: u8) -> Result<(), Chip8ErrorKind> {
+        let registers = Self::register_range(vx, vy);
+        let index = self.index as usize;
+        self.check_memory_range(index, registers.len())?;
+
+        for (offset, register) in registers.into_iter().enumerate() {
+            self.registers[register] = self.memory[index + offset];
+        }
+
+        Ok(())
+    }
+
+    /// Loads I with the 16-bit address following the instruction.
+    pub fn load_long_index(&mut self) -> Result<(), Chip8ErrorKind> {
+        let pc = self.pc as usize;
+        self.check_memory_range(pc, 2)?;
+
+        self.index = u16::from_be_bytes([self.memory[pc], self.memory[pc + 1]]);
+        self.pc = self.pc.wrapping_add(2);
+        Ok(())
+    }
+
+    pub fn select_planes(&mut self, planes: u8) {
+        self.planes = planes & 0b11;
+    }
+
+    pub fn load_audio_pattern(&mut self) -> Result<(), Chip8ErrorKind> {
+        let index = self.index as usize;
+        let len = self.audio_pattern.len();
+        self.check_memory_range(index, len)?;
+
+        self.audio_pattern
+            .copy_from_slice(&self.memory[index..index + len]);
+        self.audio_dirty = true;
+        Ok(())
+    }
+
+    pub fn load_pitch_vx(&mut self, vx: u8) {
+        self.pitch = self.registers[vx as usize];
+        self.audio_dirty = true;
+    }
+}
+
+/// 64-bit FNV-1a hash.
+fn fnv1a<'a>(bytes: impl IntoIterator<Item = &'a u8>) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xCBF2_9CE4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01B3;
+
+    bytes.into_iter().fold(FNV_OFFSET_BASIS, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+    })
 }
@@ -1,7 +1,533 @@
-use std::{env, fs::File};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::{env, net::TcpListener, path::Path};
 
-use hachi::Chip8;
-use log::error;
+use hachi::terminal::{self, Render, TerminalOptions};
+use hachi::{
+    Chip8, DEFAULT_INSTRUCTIONS_PER_FRAME, Debugger, GdbStub, GifRecorder, ImageFormat, Movie,
+    Palette, PpmSequence, Quirks, Screenshot, Syntax, Synthesizer, assemble, disassemble,
+};
+use log::{error, info};
+
+const USAGE: &str = "Usage:
+  hachi run [options] <rom-filepath>
+  hachi disasm [--syntax cowgod|octo] <rom-filepath>
+  hachi asm <source-filepath> <rom-filepath>
+  hachi debug [--ipf <n>] [--quirks <preset>] <rom-filepath>
+  hachi gdb [--port <n>] [--ipf <n>] [--quirks <preset>] <rom-filepath>
+
+Runs in the terminal by default: the keypad is mapped to 1234/QWER/ASDF/ZXCV
+and Esc quits. F5 and F9 save and load the selected save state slot, F6 and F7
+select the previous and next slot. Backspace rewinds one second.
+
+Options:
+  --headless            Run as fast as possible without a display and print the final framebuffer
+  --frames <n>          Stop after n frames (with --headless, required unless replaying or
+                        taking a screenshot at a frame)
+  --ipf <n>             Instructions executed per 60 Hz frame (default: 11)
+  --quirks <preset>     default, vip, chip48, schip or xochip (default: default)
+  --render <style>      Terminal rendering: half or braille (default: half)
+  --output <format>     Final framebuffer dump with --headless: ascii or hash (default: ascii)
+  --port <n>            Local TCP port the GDB stub listens on (default: 1234)
+  --rewind <seconds>    Rewind history kept in the terminal, 0 to disable (default: 10)
+  --seed <n>            Seed the random number generator for a reproducible run
+  --record <movie>      Record the keypad input of every frame to a movie file
+  --replay <movie>      Replay a movie file with the quirks, seed and --ipf it was recorded with
+  --screenshot <file>   Save the display as a .pbm, .ppm or .png image with --headless
+  --screenshot-at-frame <n>
+                        Take the screenshot after n frames instead of at the end, stopping
+                        there unless --frames is given
+  --clip <path>         Record every frame with --headless, as an animated GIF for paths ending
+                        in .gif and as numbered PPM images in a directory otherwise
+  --wav <file>          Record the sound of a --headless run as a WAV file
+  --scale <n>           Screenshot and clip pixel size (default: 1)
+  --palette <colors>    Up to 4 screenshot and clip colors as comma-separated hex RGB, background
+                        first (default: 000000,FFFFFF,AAAAAA,555555)";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Output {
+    Ascii,
+    Hash,
+}
+
+struct RunOptions {
+    rom_filepath: String,
+    headless: bool,
+    frames: Option<u64>,
+    instructions_per_frame: usize,
+    quirks: String,
+    render: Render,
+    output: Output,
+    port: u16,
+    rewind_seconds: usize,
+    seed: Option<u64>,
+    record: Option<String>,
+    replay: Option<String>,
+    screenshot: Option<String>,
+    clip: Option<String>,
+    wav: Option<String>,
+    screenshot_at_frame: Option<u64>,
+    scale: usize,
+    palette: Palette,
+}
+
+impl RunOptions {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = Self {
+            rom_filepath: String::new(),
+            headless: false,
+            frames: None,
+            instructions_per_frame: DEFAULT_INSTRUCTIONS_PER_FRAME,
+            quirks: "default".to_string(),
+            render: Render::default(),
+            output: Output::Ascii,
+            port: 1234,
+            rewind_seconds: 10,
+            seed: None,
+            record: None,
+            replay: None,
+            screenshot: None,
+            clip: None,
+            wav: None,
+            screenshot_at_frame: None,
+            scale: 1,
+            palette: Palette::default(),
+        };
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("Missing value for {}", arg))
+            };
+
+            match arg.as_str() {
+                "--headless" => options.headless = true,
+                "--frames" => options.frames = Some(parse_number(arg, value()?)?),
+                "--ipf" => options.instructions_per_frame = parse_number(arg, value()?)?,
+                "--quirks" => options.quirks = value()?.clone(),
+                "--port" => options.port = parse_number(arg, value()?)?,
+                "--rewind" => options.rewind_seconds = parse_number(arg, value()?)?,
+                "--seed" => options.seed = Some(parse_number(arg, value()?)?),
+                "--record" => options.record = Some(value()?.clone()),
+                "--replay" => options.replay = Some(value()?.clone()),
+                "--screenshot" => options.screenshot = Some(value()?.clone()),
+                "--clip" => options.clip = Some(value()?.clone()),
+                "--wav" => options.wav = Some(value()?.clone()),
+                "--screenshot-at-frame" => {
+                    options.screenshot_at_frame = Some(parse_number(arg, value()?)?)
+                }
+                "--scale" => options.scale = parse_number(arg, value()?)?,
+                "--palette" => {
+                    let colors = value()?;
+                    options.palette = Palette::parse(colors)
+                        .ok_or_else(|| format!("Invalid palette: {}", colors))?;
+                }
+                "--render" => {
+                    options.render = match value()?.as_str() {
+                        "half" => Render::HalfBlock,
+                        "braille" => Render::Braille,
+                        other => return Err(format!("Unknown render style: {}", other)),
+                    }
+                }
+                "--output" => {
+                    options.output = match value()?.as_str() {
+                        "ascii" => Output::Ascii,
+                        "hash" => Output::Hash,
+                        other => return Err(format!("Unknown output format: {}", other)),
+                    }
+                }
+                flag if flag.starts_with("--") => return Err(format!("Unknown option: {}", flag)),
+                rom if options.rom_filepath.is_empty() => options.rom_filepath = rom.to_string(),
+                extra => return Err(format!("Unexpected argument: {}", extra)),
+            }
+        }
+
+        if options.rom_filepath.is_empty() {
+            return Err("Missing ROM filepath".to_string());
+        }
+        if options.headless
+            && options.frames.is_none()
+            && options.replay.is_none()
+            && options.screenshot_at_frame.is_none()
+        {
+            return Err(
+                "--headless requires --frames, --replay or --screenshot-at-frame".to_string(),
+            );
+        }
+        if let Some(path) = &options.screenshot {
+            if !options.headless {
+                return Err("--screenshot requires --headless".to_string());
+            }
+            if ImageFormat::from_path(Path::new(path)).is_none() {
+                return Err(format!("Unknown screenshot format: {}", path));
+            }
+        }
+        if options.clip.is_some() && !options.headless {
+            return Err("--clip requires --headless".to_string());
+        }
+        if options.wav.is_some() && !options.headless {
+            return Err("--wav requires --headless".to_string());
+        }
+        if options.screenshot_at_frame.is_some() && options.screenshot.is_none() {
+            return Err("--screenshot-at-frame requires --screenshot".to_string());
+        }
+        if options.scale == 0 {
+            return Err("--scale must be at least 1".to_string());
+        }
+        if options.record.is_some() && options.replay.is_some() {
+            return Err("--record and --replay can't be combined".to_string());
+        }
+
+        Ok(options)
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid value for {}: {}", flag, value))
+}
+
+fn create_chip8(preset: &str) -> Result<Chip8, String> {
+    let chip8 = match preset {
+        "default" => Chip8::default(),
+        "vip" => Chip8::new(Quirks::cosmac_vip()),
+        "chip48" => Chip8::new(Quirks::chip48()),
+        "schip" => Chip8::new(Quirks::super_chip()),
+        "xochip" => Chip8::xo_chip(),
+        other => return Err(format!("Unknown quirks preset: {}", other)),
+    };
+
+    Ok(chip8)
+}
+
+fn load_rom(options: &RunOptions) -> Result<Chip8, String> {
+    let mut chip8 = create_chip8(&options.quirks)?;
+    let file =
+        File::open(&options.rom_filepath).map_err(|e| format!("Failed to open ROM file: {}", e))?;
+
+    chip8
+        .load_rom_from_reader(file)
+        .map_err(|e| format!("Failed to load ROM: {}", e))?;
+
+    Ok(chip8)
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let mut options = RunOptions::parse(args)?;
+    let rom = std::fs::read(&options.rom_filepath)
+        .map_err(|e| format!("Failed to open ROM file: {}", e))?;
+
+    let (mut chip8, mut movie) = match &options.replay {
+        Some(path) => {
+            let movie = read_movie(path)?;
+            let chip8 = movie
+                .machine(&rom)
+                .map_err(|e| format!("Failed to replay {}: {}", path, e))?;
+            options.instructions_per_frame = movie.instructions_per_frame;
+            options.frames = options
+                .frames
+                .or(options.screenshot_at_frame)
+                .or(Some(movie.len() as u64));
+            (chip8, Some(movie))
+        }
+        None => {
+            let mut chip8 = create_chip8(&options.quirks)?;
+            chip8
+                .load_rom_from_reader(rom.as_slice())
+                .map_err(|e| format!("Failed to load ROM: {}", e))?;
+
+            // Recordings are always seeded so they can be replayed
+            let seed = match (options.seed, &options.record) {
+                (Some(seed), _) => Some(seed),
+                (None, Some(_)) => Some(rand::random()),
+                (None, None) => None,
+            };
+            if let Some(seed) = seed {
+                chip8.seed_rng(seed);
+            }
+            let movie = seed
+                .filter(|_| options.record.is_some())
+                .map(|seed| Movie::new(&rom, &chip8, seed, options.instructions_per_frame));
+            (chip8, movie)
+        }
+    };
+
+    let result = if options.headless {
+        run_headless(&mut chip8, &options, movie.as_mut())
+    } else {
+        let terminal_options = TerminalOptions {
+            instructions_per_frame: options.instructions_per_frame,
+            render: options.render,
+            frames: options.frames,
+            state_path: Some(options.rom_filepath.clone().into()),
+            rewind_frames: options.rewind_seconds * 60,
+        };
+        terminal::run(&mut chip8, terminal_options, movie.as_mut())
+            .map_err(|e| format!("Emulation stopped: {}", e))
+    };
+
+    // Saved even if emulation failed, to reproduce the failure
+    if let (Some(path), Some(movie)) = (&options.record, &movie) {
+        File::create(path)
+            .and_then(|mut file| movie.write(&mut file))
+            .map_err(|e| format!("Failed to write movie file: {}", e))?;
+        info!("Recorded {} frames to {}", movie.len(), path);
+    }
+
+    result
+}
+
+fn save_screenshot(chip8: &Chip8, options: &RunOptions) -> Result<(), String> {
+    let Some(path) = &options.screenshot else {
+        return Ok(());
+    };
+    let path = Path::new(path);
+    let format = ImageFormat::from_path(path)
+        .ok_or_else(|| format!("Unknown screenshot format: {}", path.display()))?;
+    let screenshot = Screenshot {
+        format,
+        scale: options.scale,
+        palette: options.palette,
+    };
+
+    screenshot
+        .save(chip8, path)
+        .map_err(|e| format!("Failed to save screenshot: {}", e))?;
+    info!("Saved screenshot to {}", path.display());
+    Ok(())
+}
+
+const WAV_SAMPLE_RATE: u32 = 44_100;
+
+/// Gameplay clip recorded with --clip.
+enum Clip {
+    Gif(GifRecorder<BufWriter<File>>),
+    Ppm(PpmSequence),
+}
+
+impl Clip {
+    /// A GIF for paths ending in .gif, a directory of PPM images otherwise.
+    fn create(path: &str, options: &RunOptions) -> io::Result<Self> {
+        let path = Path::new(path);
+        if path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("gif"))
+        {
+            let file = BufWriter::new(File::create(path)?);
+            GifRecorder::new(file, options.scale, options.palette).map(Self::Gif)
+        } else {
+            PpmSequence::new(path, options.scale, options.palette).map(Self::Ppm)
+        }
+    }
+
+    fn capture(&mut self, chip8: &Chip8) -> io::Result<()> {
+        match self {
+            Self::Gif(gif) => gif.capture(chip8),
+            Self::Ppm(ppm) => ppm.capture(chip8),
+        }
+    }
+
+    fn finish(self) -> io::Result<u64> {
+        match self {
+            Self::Gif(gif) => {
+                let frames = gif.frames();
+                gif.finish()?.flush()?;
+                Ok(frames)
+            }
+            Self::Ppm(ppm) => Ok(ppm.frames()),
+        }
+    }
+}
+
+fn run_headless(
+    chip8: &mut Chip8,
+    options: &RunOptions,
+    movie: Option<&mut Movie>,
+) -> Result<(), String> {
+    let mut clip = options
+        .clip
+        .as_ref()
+        .map(|path| Clip::create(path, options))
+        .transpose()
+        .map_err(|e| format!("Failed to create clip: {}", e))?;
+
+    let mut synthesizer = options
+        .wav
+        .as_ref()
+        .map(|_| Synthesizer::new(WAV_SAMPLE_RATE));
+
+    let result = run_frames(chip8, options, movie, clip.as_mut(), synthesizer.as_mut());
+    // Finished even if emulation failed, to show the failure
+    if let (Some(path), Some(clip)) = (&options.clip, clip) {
+        let frames = clip
+            .finish()
+            .map_err(|e| format!("Failed to write clip: {}", e))?;
+        info!("Recorded {} frames to {}", frames, path);
+    }
+    if let (Some(path), Some(synthesizer)) = (&options.wav, synthesizer) {
+        File::create(path)
+            .map(BufWriter::new)
+            .and_then(|mut file| {
+                synthesizer.write_wav(&mut file)?;
+                file.flush()
+            })
+            .map_err(|e| format!("Failed to write WAV file: {}", e))?;
+        info!(
+            "Recorded {} samples to {}",
+            synthesizer.samples().len(),
+            path
+        );
+    }
+    let frame = result?;
+
+    match options.screenshot_at_frame {
+        None if options.screenshot.is_some() => save_screenshot(chip8, options)?,
+        Some(at) if at > frame => {
+            return Err(format!(
+                "Stopped after {} frames, before the screenshot at frame {}",
+                frame, at
+            ));
+        }
+        _ => {}
+    }
+
+    match options.output {
+        Output::Ascii => print!("{}", chip8.video_ascii()),
+        Output::Hash => println!("{:016x}", chip8.video_hash()),
+    }
+
+    Ok(())
+}
+
+/// Runs frames until the frame limit or 00FD, returning how many ran.
+fn run_frames(
+    chip8: &mut Chip8,
+    options: &RunOptions,
+    mut movie: Option<&mut Movie>,
+    mut clip: Option<&mut Clip>,
+    mut synthesizer: Option<&mut Synthesizer>,
+) -> Result<u64, String> {
+    let frames = options.frames.or(options.screenshot_at_frame);
+    let mut frame = 0;
+    if options.screenshot_at_frame == Some(0) {
+        save_screenshot(chip8, options)?;
+    }
+    while frames.is_none_or(|frames| frame < frames) && !chip8.halted {
+        if let Some(movie) = movie.as_deref_mut()
+            && !movie.play_frame(frame as usize, chip8)
+        {
+            movie.record_frame(chip8);
+        }
+        let result = match synthesizer.as_deref_mut() {
+            Some(synthesizer) => {
+                let result = chip8.run_frame_with(
+                    options.instructions_per_frame,
+                    &mut (),
+                    &mut (),
+                    synthesizer,
+                );
+                synthesizer.render_frame();
+                result
+            }
+            None => chip8.run_frame(options.instructions_per_frame),
+        };
+        if let Some(clip) = clip.as_deref_mut() {
+            clip.capture(chip8)
+                .map_err(|e| format!("Failed to write clip: {}", e))?;
+        }
+        result.map_err(|e| format!("Emulation stopped: {}", e))?;
+        frame += 1;
+        if options.screenshot_at_frame == Some(frame) {
+            save_screenshot(chip8, options)?;
+        }
+    }
+
+    Ok(frame)
+}
+
+fn read_movie(path: &str) -> Result<Movie, String> {
+    File::open(path)
+        .and_then(Movie::read)
+        .map_err(|e| format!("Failed to read movie file: {}", e))
+}
+
+fn disasm(args: &[String]) -> Result<(), String> {
+    let (syntax, rom_filepath) = match args {
+        [rom] => (Syntax::Cowgod, rom),
+        [flag, syntax, rom] if flag == "--syntax" => {
+            let syntax = match syntax.as_str() {
+                "cowgod" => Syntax::Cowgod,
+                "octo" => Syntax::Octo,
+                other => return Err(format!("Unknown syntax: {}", other)),
+            };
+            (syntax, rom)
+        }
+        _ => return Err(format!("Invalid arguments. {}", USAGE)),
+    };
+
+    let rom = std::fs::read(rom_filepath).map_err(|e| format!("Failed to open ROM file: {}", e))?;
+    // XO-CHIP memory fits any ROM
+    let mut chip8 = Chip8::xo_chip();
+    chip8
+        .load_rom_from_reader(rom.as_slice())
+        .map_err(|e| format!("Failed to load ROM: {}", e))?;
+
+    let disassembly = disassemble(&chip8.memory, chip8.pc, rom.len());
+    print!("{}", disassembly.listing(syntax));
+
+    Ok(())
+}
+
+fn asm(args: &[String]) -> Result<(), String> {
+    let [source_filepath, rom_filepath] = args else {
+        return Err(format!("Invalid arguments. {}", USAGE));
+    };
+
+    let source = std::fs::read_to_string(source_filepath)
+        .map_err(|e| format!("Failed to open source file: {}", e))?;
+    let rom = assemble(&source).map_err(|e| format!("{}:{}", source_filepath, e))?;
+    std::fs::write(rom_filepath, rom).map_err(|e| format!("Failed to write ROM file: {}", e))?;
+
+    Ok(())
+}
+
+fn debug(args: &[String]) -> Result<(), String> {
+    let options = RunOptions::parse(args)?;
+    let mut debugger = Debugger::new(load_rom(&options)?);
+    debugger.instructions_per_frame = options.instructions_per_frame;
+    debugger.state_path = Some(options.rom_filepath.into());
+
+    // Ctrl-C stops a running `continue` instead of the debugger
+    let interrupt = Arc::clone(&debugger.interrupt);
+    ctrlc::set_handler(move || interrupt.store(true, Ordering::Relaxed))
+        .map_err(|e| format!("Failed to handle Ctrl-C: {}", e))?;
+
+    debugger
+        .run(io::stdin().lock(), io::stdout())
+        .map_err(|e| format!("Debugger failed: {}", e))
+}
+
+fn gdb(args: &[String]) -> Result<(), String> {
+    let options = RunOptions::parse(args)?;
+    let mut stub = GdbStub::new(load_rom(&options)?);
+    stub.instructions_per_frame = options.instructions_per_frame;
+
+    let listener = TcpListener::bind(("127.0.0.1", options.port))
+        .map_err(|e| format!("Failed to listen on port {}: {}", options.port, e))?;
+    info!("Waiting for GDB on 127.0.0.1:{}", options.port);
+
+    let (stream, address) = listener
+        .accept()
+        .map_err(|e| format!("Failed to accept connection: {}", e))?;
+    info!("GDB connected from {}", address);
+
+    stub.serve(stream)
+        .map_err(|e| format!("GDB connection failed: {}", e))
+}
 
 fn main() {
     if std::env::var("RUST_LOG").is_err() {
@@ -11,21 +537,20 @@ fn main() {
     }
     pretty_env_logger::init();
 
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        error!("Invalid arguments. Usage: hachi <rom-filepath>");
-        std::process::exit(1);
-    }
-    let rom_filepath = &args[1];
-
-    let mut chip8 = Chip8::default();
-    let file = File::open(rom_filepath).unwrap_or_else(|e| {
-        error!("Failed to open ROM file: {}", e);
-        std::process::exit(1);
-    });
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("run") => run(&args[1..]),
+        Some("disasm") => disasm(&args[1..]),
+        Some("asm") => asm(&args[1..]),
+        Some("debug") => debug(&args[1..]),
+        Some("gdb") => gdb(&args[1..]),
+        // `hachi <rom-filepath>` is kept as a shorthand for `hachi run`
+        Some(rom) if args.len() == 1 && !rom.starts_with("--") => run(&args),
+        _ => Err(format!("Invalid arguments. {}", USAGE)),
+    };
 
-    if let Err(e) = chip8.load_rom_from_reader(file) {
-        error!("Failed to load ROM: {}", e);
+    if let Err(e) = result {
+        error!("{}", e);
         std::process::exit(1);
     }
 }
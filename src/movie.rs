@@ -0,0 +1,137 @@
+//! Movies: recordings of a session's keypad input that replay it exactly.
+//!
+//! A movie pins down everything else a session depends on (the ROM, quirks,
+//! memory size, random seed and instructions per frame), so replaying its
+//! input reproduces the session bit for bit. It's stored like a save state,
+//! with the magic `HACHIMOV`.
+
+use std::io::{self, Read, Write};
+
+use crate::save_state::{
+    Decoder, decode_quirks, encode_quirks, invalid_data, read_container, write_container,
+};
+use crate::{Chip8, MEMORY_SIZE, Quirks, XO_CHIP_MEMORY_SIZE, fnv1a};
+
+const MAGIC: &[u8; 8] = b"HACHIMOV";
+const VERSION: u16 = 1;
+
+/// A recorded session: the machine setup and the keypad state of every frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Movie {
+    /// Hash of the ROM, see [`Movie::rom_hash`].
+    pub rom_hash: u64,
+    pub quirks: Quirks,
+    pub memory_size: usize,
+    pub seed: u64,
+    pub instructions_per_frame: usize,
+    /// Keypad state at the start of each frame, bit n set while key n is held.
+    pub frames: Vec<u16>,
+}
+
+impl Movie {
+    /// An empty movie of `rom` running on a machine configured like `chip8`,
+    /// whose random number generator must be seeded with `seed`.
+    pub fn new(rom: &[u8], chip8: &Chip8, seed: u64, instructions_per_frame: usize) -> Self {
+        Self {
+            rom_hash: Self::rom_hash(rom),
+            quirks: chip8.quirks,
+            memory_size: chip8.memory.len(),
+            seed,
+            instructions_per_frame,
+            frames: Vec::new(),
+        }
+    }
+
+    /// 64-bit FNV-1a hash identifying a ROM.
+    pub fn rom_hash(rom: &[u8]) -> u64 {
+        fnv1a(rom)
+    }
+
+    /// A fresh machine set up as recorded, with `rom` loaded and the random
+    /// number generator seeded. Fails if `rom` isn't the recorded ROM.
+    pub fn machine(&self, rom: &[u8]) -> io::Result<Chip8> {
+        if Self::rom_hash(rom) != self.rom_hash {
+            return Err(invalid_data("ROM doesn't match the movie"));
+        }
+
+        let mut chip8 = Chip8::new(self.quirks);
+        chip8.memory.resize(self.memory_size, 0);
+        chip8.seed_rng(self.seed);
+        chip8.load_rom_from_reader(rom)?;
+        Ok(chip8)
+    }
+
+    /// Number of recorded frames.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Records the current keypad state of `chip8` as the next frame.
+    pub fn record_frame(&mut self, chip8: &Chip8) {
+        let keys = (0..16)
+            .filter(|&key| chip8.keypad[key])
+            .fold(0, |keys, key| keys | 1 << key);
+        self.frames.push(keys);
+    }
+
+    /// Presses and releases keys on `chip8` to match the recorded `frame`.
+    /// Returns false past the end of the movie.
+    pub fn play_frame(&self, frame: usize, chip8: &mut Chip8) -> bool {
+        let Some(&keys) = self.frames.get(frame) else {
+            return false;
+        };
+
+        chip8.set_keypad(std::array::from_fn(|key| keys & 1 << key != 0));
+
+        true
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let mut payload = Vec::with_capacity(32 + 2 * self.frames.len());
+        payload.extend_from_slice(&self.rom_hash.to_le_bytes());
+        payload.push(encode_quirks(&self.quirks));
+        payload.extend_from_slice(&(self.memory_size as u32).to_le_bytes());
+        payload.extend_from_slice(&self.seed.to_le_bytes());
+        payload.extend_from_slice(&(self.instructions_per_frame as u32).to_le_bytes());
+        payload.extend_from_slice(&(self.frames.len() as u32).to_le_bytes());
+        for keys in &self.frames {
+            payload.extend_from_slice(&keys.to_le_bytes());
+        }
+
+        write_container(writer, MAGIC, VERSION, &payload)
+    }
+
+    pub fn read<R: Read>(reader: R) -> io::Result<Self> {
+        let payload = read_container(reader, MAGIC, VERSION, "movie")?;
+        let mut decoder = Decoder { bytes: &payload };
+
+        let rom_hash = decoder.u64()?;
+        let quirks = decode_quirks(decoder.u8()?);
+        let memory_size = decoder.u32()? as usize;
+        if memory_size != MEMORY_SIZE && memory_size != XO_CHIP_MEMORY_SIZE {
+            return Err(invalid_data("invalid memory size"));
+        }
+        let seed = decoder.u64()?;
+        let instructions_per_frame = decoder.u32()? as usize;
+        let frame_count = decoder.u32()?;
+        let frames = (0..frame_count)
+            .map(|_| decoder.u16())
+            .collect::<io::Result<_>>()?;
+        if !decoder.bytes.is_empty() {
+            return Err(invalid_data("trailing bytes in payload"));
+        }
+
+        Ok(Self {
+            rom_hash,
+            quirks,
+            memory_size,
+            seed,
+            instructions_per_frame,
+            frames,
+        })
+    }
+}
@@ -0,0 +1,76 @@
+/// Behaviours that differ between CHIP-8 interpreters.
+///
+/// ROMs are usually written against one specific interpreter, so the quirks
+/// have to match it for the program to run correctly. `Quirks::default()`
+/// has every quirk disabled, matching most modern CHIP-8 interpreters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Quirks {
+    /// 8xy6/8xyE shift Vy and store the result in Vx, instead of shifting Vx in place.
+    pub shift_uses_vy: bool,
+    /// Fx55/Fx65 leave I pointing past the last register read or written.
+    pub memory_increments_index: bool,
+    /// Bnnn jumps to nnn + Vx (with x the highest nibble of nnn) instead of nnn + V0.
+    pub jump_uses_vx: bool,
+    /// Sprites are clipped at the screen edges instead of wrapping around.
+    pub clip_sprites: bool,
+    /// 8xy1/8xy2/8xy3 reset VF to 0.
+    pub vf_reset: bool,
+    /// Dxyn waits for the vertical blank, allowing at most one draw per frame.
+    pub display_wait: bool,
+    /// Dxy0 draws nothing instead of a 16x16 sprite.
+    pub dxy0_draws_nothing: bool,
+}
+
+impl Quirks {
+    /// The original interpreter on the RCA COSMAC VIP.
+    pub fn cosmac_vip() -> Self {
+        Self {
+            shift_uses_vy: true,
+            memory_increments_index: true,
+            jump_uses_vx: false,
+            clip_sprites: true,
+            vf_reset: true,
+            display_wait: true,
+            dxy0_draws_nothing: true,
+        }
+    }
+
+    /// CHIP-48 on the HP-48 calculators.
+    pub fn chip48() -> Self {
+        Self {
+            shift_uses_vy: false,
+            memory_increments_index: false,
+            jump_uses_vx: true,
+            clip_sprites: true,
+            vf_reset: false,
+            display_wait: false,
+            dxy0_draws_nothing: true,
+        }
+    }
+
+    /// SUPER-CHIP 1.1.
+    pub fn super_chip() -> Self {
+        Self {
+            shift_uses_vy: false,
+            memory_increments_index: false,
+            jump_uses_vx: true,
+            clip_sprites: true,
+            vf_reset: false,
+            display_wait: false,
+            dxy0_draws_nothing: false,
+        }
+    }
+
+    /// XO-CHIP, as implemented by Octo.
+    pub fn xo_chip() -> Self {
+        Self {
+            shift_uses_vy: true,
+            memory_increments_index: true,
+            jump_uses_vx: false,
+            clip_sprites: false,
+            vf_reset: false,
+            display_wait: false,
+            dxy0_draws_nothing: false,
+        }
+    }
+}
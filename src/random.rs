@@ -0,0 +1,113 @@
+/// Source of the random bytes returned by `Cxkk`.
+///
+/// Sources have their whole state in a `u64`, so that save states and the
+/// rewind history can restore it and replays stay deterministic.
+pub trait RandomSource: Send + Sync {
+    fn next_byte(&mut self) -> u8;
+
+    /// The current state, from which [`RandomSource::set_state`] continues
+    /// the same sequence.
+    fn state(&self) -> u64;
+
+    fn set_state(&mut self, state: u64);
+
+    /// Clones the source behind a trait object.
+    fn clone_box(&self) -> Box<dyn RandomSource>;
+}
+
+impl Clone for Box<dyn RandomSource> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// The default source: a xorshift64* generator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Xorshift {
+    state: u64,
+}
+
+impl Xorshift {
+    /// A generator producing the same sequence for the same seed.
+    pub fn new(seed: u64) -> Self {
+        // Scramble the seed with SplitMix64 so that close seeds give
+        // unrelated sequences
+        let z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        let z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+
+        let mut xorshift = Self { state: 0 };
+        xorshift.set_state(z ^ (z >> 31));
+        xorshift
+    }
+
+    /// A generator seeded from the operating system's entropy.
+    pub fn from_entropy() -> Self {
+        Self::new(rand::random())
+    }
+}
+
+impl RandomSource for Xorshift {
+    fn next_byte(&mut self) -> u8 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+
+        // The high bits are the most random
+        (self.state.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 56) as u8
+    }
+
+    fn state(&self) -> u64 {
+        self.state
+    }
+
+    fn set_state(&mut self, state: u64) {
+        // xorshift gets stuck on 0
+        self.state = if state == 0 {
+            0x9E37_79B9_7F4A_7C15
+        } else {
+            state
+        };
+    }
+
+    fn clone_box(&self) -> Box<dyn RandomSource> {
+        Box::new(*self)
+    }
+}
+
+/// Repeats a fixed sequence of bytes, for tests.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sequence {
+    bytes: Vec<u8>,
+    position: usize,
+}
+
+impl Sequence {
+    /// # Panics
+    ///
+    /// If `bytes` is empty.
+    pub fn new(bytes: Vec<u8>) -> Self {
+        assert!(!bytes.is_empty(), "Sequence needs at least one byte");
+        Self { bytes, position: 0 }
+    }
+}
+
+impl RandomSource for Sequence {
+    fn next_byte(&mut self) -> u8 {
+        let byte = self.bytes[self.position];
+        self.position = (self.position + 1) % self.bytes.len();
+        byte
+    }
+
+    fn state(&self) -> u64 {
+        self.position as u64
+    }
+
+    fn set_state(&mut self, state: u64) {
+        self.position = state as usize % self.bytes.len();
+    }
+
+    fn clone_box(&self) -> Box<dyn RandomSource> {
+        Box::new(self.clone())
+    }
+}
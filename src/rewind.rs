@@ -0,0 +1,170 @@
+//! Rewind history: a ring buffer of delta-compressed machine snapshots.
+
+use std::collections::VecDeque;
+
+use crate::Chip8;
+
+// Unchanged runs shorter than this are cheaper to store inside a literal run.
+const MIN_UNCHANGED_RUN: usize = 4;
+
+/// Bounded history of machine states, newest last, that can be popped to
+/// rewind execution.
+///
+/// Only the newest snapshot is stored in full. Each older one is stored as
+/// the difference from the snapshot after it, which is tiny since most of
+/// memory and the display stay the same from one frame to the next. When
+/// the history is full, the oldest snapshot is dropped.
+#[derive(Debug, Clone, Default)]
+pub struct Rewind {
+    capacity: usize,
+    latest: Option<Vec<u8>>,
+    /// Turns each snapshot into the one before it, oldest first.
+    deltas: VecDeque<Vec<u8>>,
+}
+
+impl Rewind {
+    /// A history keeping up to `capacity` snapshots.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            ..Default::default()
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of snapshots that can be popped.
+    pub fn len(&self) -> usize {
+        self.latest.as_ref().map_or(0, |_| self.deltas.len() + 1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.latest.is_none()
+    }
+
+    /// Approximate memory used by the snapshots, in bytes.
+    pub fn size(&self) -> usize {
+        self.latest.as_ref().map_or(0, Vec::len) + self.deltas.iter().map(Vec::len).sum::<usize>()
+    }
+
+    pub fn clear(&mut self) {
+        self.latest = None;
+        self.deltas.clear();
+    }
+
+    /// Records the current state of `chip8` as the newest snapshot.
+    pub fn push(&mut self, chip8: &Chip8) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let snapshot = chip8.encode_state();
+        if let Some(latest) = self.latest.take() {
+            if latest.len() == snapshot.len() {
+                self.deltas.push_back(encode_delta(&snapshot, &latest));
+            } else {
+                // The memory size changed, older snapshots can't be diffed against this one
+                self.deltas.clear();
+            }
+        }
+        self.latest = Some(snapshot);
+
+        while self.len() > self.capacity {
+            self.deltas.pop_front();
+        }
+    }
+
+    /// Restores `chip8` to the newest snapshot and removes it from the
+    /// history. Returns false, leaving `chip8` untouched, if the history is
+    /// empty.
+    pub fn pop(&mut self, chip8: &mut Chip8) -> bool {
+        let Some(mut snapshot) = self.latest.take() else {
+            return false;
+        };
+        let restored = chip8.decode_state(&snapshot).is_ok();
+
+        if let Some(delta) = self.deltas.pop_back() {
+            apply_delta(&mut snapshot, &delta);
+            self.latest = Some(snapshot);
+        }
+
+        restored
+    }
+
+    /// Pops up to `n` snapshots, leaving `chip8` in the state recorded `n`
+    /// pushes ago, and returns how many were popped.
+    pub fn rewind(&mut self, chip8: &mut Chip8, n: usize) -> usize {
+        (0..n).take_while(|_| self.pop(chip8)).count()
+    }
+}
+
+/// Encodes the bytes of `to` that differ from `from`, which must be the
+/// same length, as pairs of runs: the length of an unchanged run, then the
+/// length and XOR of a changed run. Lengths are LEB128 varints.
+fn encode_delta(from: &[u8], to: &[u8]) -> Vec<u8> {
+    let xor: Vec<u8> = from.iter().zip(to).map(|(a, b)| a ^ b).collect();
+    let mut delta = Vec::new();
+    let mut position = 0;
+
+    while position < xor.len() {
+        let unchanged = xor[position..]
+            .iter()
+            .take_while(|&&byte| byte == 0)
+            .count();
+        position += unchanged;
+
+        let changed = xor[position..]
+            .windows(MIN_UNCHANGED_RUN)
+            .position(|window| window.iter().all(|&byte| byte == 0))
+            .unwrap_or(xor.len() - position);
+
+        push_varint(&mut delta, unchanged);
+        push_varint(&mut delta, changed);
+        delta.extend_from_slice(&xor[position..position + changed]);
+        position += changed;
+    }
+
+    delta
+}
+
+/// Applies a delta from [`encode_delta`], turning `from` into `to`.
+fn apply_delta(bytes: &mut [u8], mut delta: &[u8]) {
+    let mut position = 0;
+
+    while !delta.is_empty() {
+        position += read_varint(&mut delta);
+        let changed = read_varint(&mut delta);
+        let (xor, rest) = delta.split_at(changed);
+        for (byte, xor) in bytes[position..position + changed].iter_mut().zip(xor) {
+            *byte ^= xor;
+        }
+        position += changed;
+        delta = rest;
+    }
+}
+
+fn push_varint(bytes: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn read_varint(bytes: &mut &[u8]) -> usize {
+    let mut value = 0;
+    let mut shift = 0;
+
+    while let Some((&byte, rest)) = bytes.split_first() {
+        *bytes = rest;
+        value |= ((byte & 0x7F) as usize) << shift;
+        shift += 7;
+        if byte & 0x80 == 0 {
+            break;
+        }
+    }
+
+    value
+}
@@ -0,0 +1,286 @@
+//! Versioned binary save states.
+//!
+//! A save state starts with the magic `HACHISAV` and a `u16` format version,
+//! followed by the payload length as a `u32`, the payload and the CRC-32 of
+//! the payload. All multi-byte values are little-endian.
+
+use std::io::{self, Error, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::{Chip8, KeyWait, MEMORY_SIZE, Quirks, XO_CHIP_MEMORY_SIZE};
+
+const MAGIC: &[u8; 8] = b"HACHISAV";
+const VERSION: u16 = 1;
+
+/// Path of the numbered save state `slot` for the ROM at `rom_path`, e.g.
+/// `pong.state3` for slot 3 of `pong.ch8`.
+pub fn state_slot_path(rom_path: &Path, slot: u8) -> PathBuf {
+    rom_path.with_extension(format!("state{}", slot))
+}
+
+impl Chip8 {
+    /// Writes the complete machine state, including timers, quirks and the
+    /// random number generator's state.
+    pub fn save_state<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_container(writer, MAGIC, VERSION, &self.encode_state())
+    }
+
+    /// Restores a state written by [`Chip8::save_state`].
+    ///
+    /// The state is fully validated first: on error the machine is left
+    /// untouched.
+    pub fn load_state<R: Read>(&mut self, reader: R) -> io::Result<()> {
+        let payload = read_container(reader, MAGIC, VERSION, "save state")?;
+        self.decode_state(&payload)
+    }
+
+    /// The save state payload, without header and checksum.
+    pub(crate) fn encode_state(&self) -> Vec<u8> {
+        let mut state = Vec::with_capacity(self.memory.len() + self.video.len() + 256);
+
+        state.extend_from_slice(&self.registers);
+        state.extend_from_slice(&self.index.to_le_bytes());
+        state.extend_from_slice(&self.pc.to_le_bytes());
+        for address in self.stack {
+            state.extend_from_slice(&address.to_le_bytes());
+        }
+        state.push(self.sp);
+        state.extend(self.keypad.map(u8::from));
+        state.extend_from_slice(&match self.key_wait {
+            KeyWait::Idle => [0, 0, 0],
+            KeyWait::Waiting { vx } => [1, vx, 0],
+            KeyWait::Pressed { vx, key } => [2, vx, key],
+        });
+        state.push(self.planes);
+        state.push(self.hires.into());
+        state.push(self.delay_timer);
+        state.push(self.sound_timer);
+        state.push(self.waiting_for_vblank.into());
+        state.push(self.halted.into());
+        state.extend_from_slice(&(self.frame_cycles as u32).to_le_bytes());
+        state.extend_from_slice(&self.rpl_flags);
+        state.extend_from_slice(&self.audio_pattern);
+        state.push(self.pitch);
+        state.push(encode_quirks(&self.quirks));
+        state.extend_from_slice(&self.opcode.to_le_bytes());
+        state.extend_from_slice(&self.random.state().to_le_bytes());
+        state.extend_from_slice(&self.video);
+        state.extend_from_slice(&(self.memory.len() as u32).to_le_bytes());
+        state.extend_from_slice(&self.memory);
+
+        state
+    }
+
+    /// Restores a payload from [`Chip8::encode_state`].
+    pub(crate) fn decode_state(&mut self, payload: &[u8]) -> io::Result<()> {
+        let mut decoder = Decoder { bytes: payload };
+
+        let registers = decoder.array()?;
+        let index = decoder.u16()?;
+        let pc = decoder.u16()?;
+        let mut stack = [0; 16];
+        for address in &mut stack {
+            *address = decoder.u16()?;
+        }
+        let sp = decoder.u8()?;
+        if sp as usize > stack.len() {
+            return Err(invalid_data("stack pointer out of range"));
+        }
+        let keypad = decoder.array::<16>()?.map(|key| key != 0);
+        let key_wait = match decoder.array()? {
+            [0, _, _] => KeyWait::Idle,
+            [1, vx, _] if vx < 16 => KeyWait::Waiting { vx },
+            [2, vx, key] if vx < 16 && key < 16 => KeyWait::Pressed { vx, key },
+            _ => return Err(invalid_data("invalid key wait")),
+        };
+        let planes = decoder.u8()?;
+        let hires = decoder.bool()?;
+        let delay_timer = decoder.u8()?;
+        let sound_timer = decoder.u8()?;
+        let waiting_for_vblank = decoder.bool()?;
+        let halted = decoder.bool()?;
+        let frame_cycles = decoder.u32()? as usize;
+        let rpl_flags = decoder.array()?;
+        let audio_pattern = decoder.array()?;
+        let pitch = decoder.u8()?;
+        let quirks = decode_quirks(decoder.u8()?);
+        let opcode = decoder.u16()?;
+        let random_state = decoder.u64()?;
+        let video = decoder.array()?;
+        let memory_len = decoder.u32()? as usize;
+        if memory_len != MEMORY_SIZE && memory_len != XO_CHIP_MEMORY_SIZE {
+            return Err(invalid_data("invalid memory size"));
+        }
+        let memory = decoder.take(memory_len)?.to_vec();
+        if !decoder.bytes.is_empty() {
+            return Err(invalid_data("trailing bytes in payload"));
+        }
+
+        self.registers = registers;
+        self.memory = memory;
+        self.index = index;
+        self.pc = pc;
+        self.stack = stack;
+        self.sp = sp;
+        self.keypad = keypad;
+        self.key_wait = key_wait;
+        self.video = video;
+        self.planes = planes;
+        self.hires = hires;
+        self.delay_timer = delay_timer;
+        self.sound_timer = sound_timer;
+        self.waiting_for_vblank = waiting_for_vblank;
+        self.halted = halted;
+        self.frame_cycles = frame_cycles;
+        self.rpl_flags = rpl_flags;
+        self.audio_pattern = audio_pattern;
+        self.pitch = pitch;
+        self.quirks = quirks;
+        self.opcode = opcode;
+        self.random.set_state(random_state);
+        self.display_dirty = true;
+        self.audio_dirty = true;
+
+        Ok(())
+    }
+}
+
+/// Writes `payload` with a header (`magic`, `version` and length) and a
+/// trailing CRC-32.
+pub(crate) fn write_container<W: Write>(
+    writer: &mut W,
+    magic: &[u8; 8],
+    version: u16,
+    payload: &[u8],
+) -> io::Result<()> {
+    writer.write_all(magic)?;
+    writer.write_all(&version.to_le_bytes())?;
+    writer.write_all(&(payload.len() as u32).to_le_bytes())?;
+    writer.write_all(payload)?;
+    writer.write_all(&crc32(payload).to_le_bytes())
+}
+
+/// Reads and checks a payload written by [`write_container`]; `name`
+/// describes the format in errors.
+pub(crate) fn read_container<R: Read>(
+    mut reader: R,
+    magic: &[u8; 8],
+    version: u16,
+    name: &str,
+) -> io::Result<Vec<u8>> {
+    let mut header = [0; 14];
+    reader.read_exact(&mut header)?;
+    if &header[..8] != magic {
+        return Err(invalid_data(&format!("not a {}", name)));
+    }
+    let found_version = u16::from_le_bytes([header[8], header[9]]);
+    if found_version != version {
+        return Err(invalid_data(&format!(
+            "unsupported {} version {}",
+            name, found_version
+        )));
+    }
+
+    let len = u32::from_le_bytes([header[10], header[11], header[12], header[13]]);
+    // Read as far as the data goes, rather than trusting the length to
+    // allocate
+    let mut payload = Vec::new();
+    reader.by_ref().take(len as u64).read_to_end(&mut payload)?;
+    if payload.len() != len as usize {
+        return Err(Error::new(
+            ErrorKind::UnexpectedEof,
+            format!("{} truncated", name),
+        ));
+    }
+    let mut checksum = [0; 4];
+    reader.read_exact(&mut checksum)?;
+    if u32::from_le_bytes(checksum) != crc32(&payload) {
+        return Err(invalid_data(&format!("{} checksum mismatch", name)));
+    }
+
+    Ok(payload)
+}
+
+/// Reads values from the front of a payload.
+pub(crate) struct Decoder<'a> {
+    pub(crate) bytes: &'a [u8],
+}
+
+impl Decoder<'_> {
+    pub(crate) fn take(&mut self, len: usize) -> io::Result<&[u8]> {
+        let Some((taken, rest)) = self.bytes.split_at_checked(len) else {
+            return Err(invalid_data("payload truncated"));
+        };
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    pub(crate) fn array<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    pub(crate) fn u8(&mut self) -> io::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(crate) fn u16(&mut self) -> io::Result<u16> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    pub(crate) fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    pub(crate) fn u64(&mut self) -> io::Result<u64> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    pub(crate) fn bool(&mut self) -> io::Result<bool> {
+        Ok(self.u8()? != 0)
+    }
+}
+
+/// Packs the quirks into one bit each, in declaration order.
+pub(crate) fn encode_quirks(quirks: &Quirks) -> u8 {
+    [
+        quirks.shift_uses_vy,
+        quirks.memory_increments_index,
+        quirks.jump_uses_vx,
+        quirks.clip_sprites,
+        quirks.vf_reset,
+        quirks.display_wait,
+        quirks.dxy0_draws_nothing,
+    ]
+    .iter()
+    .enumerate()
+    .fold(0, |bits, (bit, &enabled)| bits | (enabled as u8) << bit)
+}
+
+pub(crate) fn decode_quirks(bits: u8) -> Quirks {
+    let enabled = |bit: u8| bits & 1 << bit != 0;
+
+    Quirks {
+        shift_uses_vy: enabled(0),
+        memory_increments_index: enabled(1),
+        jump_uses_vx: enabled(2),
+        clip_sprites: enabled(3),
+        vf_reset: enabled(4),
+        display_wait: enabled(5),
+        dxy0_draws_nothing: enabled(6),
+    }
+}
+
+/// CRC-32 (IEEE 802.3), as used by zip and PNG.
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0, |crc, &byte| {
+        (0..8).fold(crc ^ byte as u32, |crc, _| {
+            (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg())
+        })
+    })
+}
+
+pub(crate) fn invalid_data(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
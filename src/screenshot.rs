@@ -0,0 +1,173 @@
+//! Screenshots of the display as PBM, PPM or PNG images.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Error, ErrorKind, Write};
+use std::path::Path;
+
+use crate::Chip8;
+
+/// Image file format of a [`Screenshot`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    /// Binary Netpbm bitmap: lit pixels are white, whatever the palette.
+    Pbm,
+    /// Binary Netpbm RGB image.
+    Ppm,
+    Png,
+}
+
+impl ImageFormat {
+    /// The format named by the extension of `path` (`pbm`, `ppm` or `png`).
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+
+        match extension.as_str() {
+            "pbm" => Some(Self::Pbm),
+            "ppm" => Some(Self::Ppm),
+            "png" => Some(Self::Png),
+            _ => None,
+        }
+    }
+}
+
+/// RGB colors of the pixel values in `chip8.video`, one bit per bitplane:
+/// 0 is the background, 1 the first plane, 2 the second and 3 both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette(pub [[u8; 3]; 4]);
+
+impl Palette {
+    /// Parses up to 4 comma-separated hex colors such as `000000,FFFFFF`,
+    /// starting with the background. Colors not given keep their default.
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut palette = Self::default();
+        let colors: Vec<&str> = text.split(',').collect();
+        if colors.len() > palette.0.len() {
+            return None;
+        }
+
+        for (color, text) in palette.0.iter_mut().zip(colors) {
+            let text = text.trim().trim_start_matches('#');
+            if text.len() != 6 {
+                return None;
+            }
+            let rgb = u32::from_str_radix(text, 16).ok()?;
+            *color = [(rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8];
+        }
+
+        Some(palette)
+    }
+
+    /// The RGB bytes of `pixels`.
+    pub(crate) fn rgb(&self, pixels: &[u8]) -> Vec<u8> {
+        pixels
+            .iter()
+            .flat_map(|&pixel| self.0[pixel as usize & 0b11])
+            .collect()
+    }
+}
+
+impl Default for Palette {
+    /// Black background, white for the first plane, light gray for the
+    /// second and dark gray where both overlap.
+    fn default() -> Self {
+        Self([
+            [0x00, 0x00, 0x00],
+            [0xFF, 0xFF, 0xFF],
+            [0xAA, 0xAA, 0xAA],
+            [0x55, 0x55, 0x55],
+        ])
+    }
+}
+
+/// Settings for writing the visible display as an image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Screenshot {
+    pub format: ImageFormat,
+    /// Width and height in image pixels of each display pixel.
+    pub scale: usize,
+    pub palette: Palette,
+}
+
+impl Screenshot {
+    /// A screenshot at scale 1 with the default palette.
+    pub fn new(format: ImageFormat) -> Self {
+        Self {
+            format,
+            scale: 1,
+            palette: Palette::default(),
+        }
+    }
+
+    /// Image width and height of the display of `chip8`.
+    pub fn size(&self, chip8: &Chip8) -> (usize, usize) {
+        (
+            chip8.video_width() * self.scale,
+            chip8.video_height() * self.scale,
+        )
+    }
+
+    pub fn write<W: Write>(&self, chip8: &Chip8, writer: &mut W) -> io::Result<()> {
+        if self.scale == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "scale must be at least 1",
+            ));
+        }
+        let (width, height) = self.size(chip8);
+        let pixels = sample(chip8, width, height);
+
+        match self.format {
+            ImageFormat::Pbm => {
+                write!(writer, "P4\n{} {}\n", width, height)?;
+                // Rows are padded to whole bytes, with 1 for black
+                for row in pixels.chunks(width) {
+                    let packed: Vec<u8> = row
+                        .chunks(8)
+                        .map(|bits| {
+                            bits.iter()
+                                .enumerate()
+                                .filter(|&(_, &pixel)| pixel == 0)
+                                .fold(0, |byte, (bit, _)| byte | 0x80 >> bit)
+                        })
+                        .collect();
+                    writer.write_all(&packed)?;
+                }
+                Ok(())
+            }
+            ImageFormat::Ppm => {
+                write!(writer, "P6\n{} {}\n255\n", width, height)?;
+                writer.write_all(&self.palette.rgb(&pixels))
+            }
+            ImageFormat::Png => {
+                let mut encoder = png::Encoder::new(writer, width as u32, height as u32);
+                encoder.set_color(png::ColorType::Indexed);
+                encoder.set_depth(png::BitDepth::Eight);
+                encoder.set_palette(self.palette.0.as_flattened());
+                let mut png = encoder.write_header().map_err(Error::other)?;
+                let indices: Vec<u8> = pixels.iter().map(|&pixel| pixel & 0b11).collect();
+                png.write_image_data(&indices).map_err(Error::other)?;
+                png.finish().map_err(Error::other)
+            }
+        }
+    }
+
+    /// Writes the screenshot to `path`.
+    pub fn save(&self, chip8: &Chip8, path: &Path) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        self.write(chip8, &mut file)?;
+        file.flush()
+    }
+}
+
+/// The visible display of `chip8` resampled to `width` x `height`, as pixel
+/// values row by row.
+pub(crate) fn sample(chip8: &Chip8, width: usize, height: usize) -> Vec<u8> {
+    let (video_width, video_height) = (chip8.video_width(), chip8.video_height());
+
+    (0..height)
+        .flat_map(|y| {
+            let row = y * video_height / height * video_width;
+            (0..width).map(move |x| chip8.video[row + x * video_width / width])
+        })
+        .collect()
+}
@@ -0,0 +1,488 @@
+//! ANSI terminal frontend: renders the display with Unicode block or braille
+//! characters and maps the keyboard onto the CHIP-8 keypad.
+
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{
+    self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, KeyboardEnhancementFlags,
+    PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+};
+use crossterm::{cursor, execute, queue, style, terminal};
+
+use crate::frontend::{Audio, Display, Input};
+use crate::{Chip8, Movie, Rewind, state_slot_path};
+
+const FRAME_DURATION: Duration = Duration::from_micros(1_000_000 / 60);
+
+// Most terminals only report key presses. Without release events a key is
+// considered released once it hasn't been reported (or auto-repeated) for this long.
+const KEY_HOLD_DURATION: Duration = Duration::from_millis(150);
+
+const STATE_SLOTS: u8 = 10;
+
+// Frames undone by each press of Backspace
+const REWIND_STEP: usize = 60;
+
+/// The conventional mapping of the COSMAC VIP hex keypad onto the left side
+/// of a QWERTY keyboard.
+///
+/// ```text
+/// 1 2 3 C      1 2 3 4
+/// 4 5 6 D  <-  Q W E R
+/// 7 8 9 E      A S D F
+/// A 0 B F      Z X C V
+/// ```
+pub fn key_for_char(c: char) -> Option<u8> {
+    let key = match c.to_ascii_lowercase() {
+        '1' => 0x1,
+        '2' => 0x2,
+        '3' => 0x3,
+        '4' => 0xC,
+        'q' => 0x4,
+        'w' => 0x5,
+        'e' => 0x6,
+        'r' => 0xD,
+        'a' => 0x7,
+        's' => 0x8,
+        'd' => 0x9,
+        'f' => 0xE,
+        'z' => 0xA,
+        'x' => 0x0,
+        'c' => 0xB,
+        'v' => 0xF,
+        _ => return None,
+    };
+
+    Some(key)
+}
+
+/// How pixels are packed into terminal character cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Render {
+    /// 1x2 pixels per cell using `▀`, `▄` and `█`.
+    #[default]
+    HalfBlock,
+    /// 2x4 pixels per cell using braille patterns (U+2800-U+28FF).
+    Braille,
+}
+
+impl Render {
+    /// Renders the visible display, one line per row of character cells.
+    /// Pixels lit on any bitplane are drawn.
+    pub fn render(self, chip8: &Chip8) -> String {
+        match self {
+            Self::HalfBlock => {
+                render_cells(chip8, 1, 2, |bits| [' ', '▀', '▄', '█'][bits as usize])
+            }
+            Self::Braille => render_cells(chip8, 2, 4, |bits| {
+                // Cell bits are numbered row by row; braille dots are numbered
+                // down the left column, then the right, with the bottom row last.
+                const DOTS: [u8; 8] = [0x01, 0x08, 0x02, 0x10, 0x04, 0x20, 0x40, 0x80];
+                let dots = (0..8)
+                    .filter(|bit| bits & 1 << bit != 0)
+                    .fold(0, |dots, bit| dots | DOTS[bit]);
+                char::from_u32(0x2800 + dots as u32).unwrap_or(' ')
+            }),
+        }
+    }
+}
+
+/// Splits the display into `cell_width` x `cell_height` cells and maps each
+/// to a character from its lit pixels, numbered row by row from the top left.
+fn render_cells(
+    chip8: &Chip8,
+    cell_width: usize,
+    cell_height: usize,
+    to_char: impl Fn(u16) -> char,
+) -> String {
+    let (width, height) = (chip8.video_width(), chip8.video_height());
+    let mut text = String::new();
+
+    for cell_y in (0..height).step_by(cell_height) {
+        for cell_x in (0..width).step_by(cell_width) {
+            let mut bits = 0;
+            for dy in 0..cell_height {
+                for dx in 0..cell_width {
+                    if chip8.video[(cell_y + dy) * width + cell_x + dx] != 0 {
+                        bits |= 1 << (dy * cell_width + dx);
+                    }
+                }
+            }
+            text.push(to_char(bits));
+        }
+        text.push('\n');
+    }
+
+    text
+}
+
+/// Settings for [`run`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TerminalOptions {
+    pub instructions_per_frame: usize,
+    pub render: Render,
+    /// Stop after this many frames; run until Esc or 00FD otherwise.
+    pub frames: Option<u64>,
+    /// ROM path the numbered save state slots are named after, see
+    /// [`state_slot_path`]. Save states are disabled without it.
+    pub state_path: Option<PathBuf>,
+    /// Frames of history kept for rewinding with Backspace, 0 to disable.
+    pub rewind_frames: usize,
+}
+
+/// Numbered save state slots, saved with F5 and loaded with F9. F6 and F7
+/// select the previous and next slot.
+struct Slots {
+    path: Option<PathBuf>,
+    selected: u8,
+    /// Result of the last slot action, shown below the display.
+    status: String,
+}
+
+impl Slots {
+    /// Handles slot keys, returning whether the key was one.
+    fn handle_key(&mut self, chip8: &mut Chip8, key_event: &KeyEvent) -> bool {
+        if key_event.kind == KeyEventKind::Release {
+            return matches!(key_event.code, KeyCode::F(5 | 6 | 7 | 9));
+        }
+
+        self.status = match key_event.code {
+            KeyCode::F(5) => self.save(chip8),
+            KeyCode::F(9) => self.load(chip8),
+            KeyCode::F(6) => {
+                self.selected = (self.selected + STATE_SLOTS - 1) % STATE_SLOTS;
+                format!("Slot {}", self.selected)
+            }
+            KeyCode::F(7) => {
+                self.selected = (self.selected + 1) % STATE_SLOTS;
+                format!("Slot {}", self.selected)
+            }
+            _ => return false,
+        };
+
+        true
+    }
+
+    fn save(&self, chip8: &Chip8) -> String {
+        let Some(path) = &self.path else {
+            return "Save states are disabled".to_string();
+        };
+
+        let result = File::create(state_slot_path(path, self.selected))
+            .and_then(|mut file| chip8.save_state(&mut file));
+        match result {
+            Ok(()) => format!("Saved slot {}", self.selected),
+            Err(e) => format!("Failed to save slot {}: {}", self.selected, e),
+        }
+    }
+
+    fn load(&self, chip8: &mut Chip8) -> String {
+        let Some(path) = &self.path else {
+            return "Save states are disabled".to_string();
+        };
+
+        let result = File::open(state_slot_path(path, self.selected))
+            .and_then(|file| chip8.load_state(file));
+        match result {
+            Ok(()) => format!("Loaded slot {}", self.selected),
+            Err(e) => format!("Failed to load slot {}: {}", self.selected, e),
+        }
+    }
+}
+
+/// Restores the terminal when dropped, including on early returns.
+struct RawTerminal {
+    release_events: bool,
+}
+
+impl RawTerminal {
+    fn enter(out: &mut impl Write) -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(
+            out,
+            terminal::EnterAlternateScreen,
+            cursor::Hide,
+            terminal::Clear(terminal::ClearType::All)
+        )?;
+
+        let release_events = terminal::supports_keyboard_enhancement().unwrap_or(false);
+        if release_events {
+            execute!(
+                out,
+                PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES)
+            )?;
+        }
+
+        Ok(Self { release_events })
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        let mut out = io::stdout();
+        if self.release_events {
+            let _ = execute!(out, PopKeyboardEnhancementFlags);
+        }
+        let _ = execute!(out, cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Draws the display, only writing to the terminal when the rendered
+/// text changes.
+struct Screen {
+    render: Render,
+    last_render: String,
+    /// Set when the whole screen was cleared, including the status line.
+    cleared: bool,
+    error: Option<io::Error>,
+}
+
+impl Screen {
+    fn draw(&mut self, chip8: &Chip8) -> io::Result<()> {
+        let mut out = io::stdout();
+        let render = self.render.render(chip8);
+        if render == self.last_render {
+            return Ok(());
+        }
+
+        // The resolution changed
+        if render.lines().count() != self.last_render.lines().count() {
+            queue!(out, terminal::Clear(terminal::ClearType::All))?;
+            self.cleared = true;
+        }
+        for (row, line) in render.lines().enumerate() {
+            queue!(out, cursor::MoveTo(0, row as u16), style::Print(line))?;
+        }
+        self.last_render = render;
+        Ok(())
+    }
+
+    fn height(&self) -> u16 {
+        self.last_render.lines().count() as u16
+    }
+}
+
+impl Display for Screen {
+    fn present(&mut self, chip8: &Chip8) {
+        if let Err(e) = self.draw(chip8) {
+            self.error.get_or_insert(e);
+        }
+    }
+}
+
+/// Keypad state from key events.
+struct Keyboard {
+    held: [bool; 16],
+    /// Keys released in the same frame they were pressed, held for that
+    /// frame so that the press isn't lost.
+    pending_release: [bool; 16],
+    pressed_this_frame: [bool; 16],
+    /// Without release events, when each key was last reported.
+    last_seen: Option<[Option<Instant>; 16]>,
+}
+
+impl Keyboard {
+    fn new(release_events: bool) -> Self {
+        Self {
+            held: [false; 16],
+            pending_release: [false; 16],
+            pressed_this_frame: [false; 16],
+            last_seen: (!release_events).then_some([None; 16]),
+        }
+    }
+
+    fn handle_key(&mut self, key_event: &KeyEvent) {
+        let KeyCode::Char(c) = key_event.code else {
+            return;
+        };
+        let Some(key) = key_for_char(c) else {
+            return;
+        };
+        let key = key as usize;
+
+        if key_event.kind == KeyEventKind::Release {
+            if self.pressed_this_frame[key] {
+                self.pending_release[key] = true;
+            } else {
+                self.held[key] = false;
+            }
+        } else {
+            self.held[key] = true;
+            self.pressed_this_frame[key] = true;
+            self.pending_release[key] = false;
+            if let Some(last_seen) = &mut self.last_seen {
+                last_seen[key] = Some(Instant::now());
+            }
+        }
+    }
+}
+
+impl Input for Keyboard {
+    fn keypad(&mut self) -> Option<[bool; 16]> {
+        if let Some(last_seen) = &mut self.last_seen {
+            for (key, seen) in last_seen.iter_mut().enumerate() {
+                if seen.is_some_and(|seen| seen.elapsed() >= KEY_HOLD_DURATION) {
+                    self.held[key] = false;
+                    *seen = None;
+                }
+            }
+        }
+
+        let keypad = self.held;
+        for key in 0..16 {
+            if self.pending_release[key] {
+                self.held[key] = false;
+            }
+        }
+        self.pending_release = [false; 16];
+        self.pressed_this_frame = [false; 16];
+
+        Some(keypad)
+    }
+}
+
+/// Rings the terminal bell when the sound starts.
+struct Bell;
+
+impl Audio for Bell {
+    fn set_playing(&mut self, playing: bool) {
+        if playing {
+            let _ = queue!(io::stdout(), style::Print('\x07'));
+        }
+    }
+}
+
+/// Runs `chip8` in the terminal at 60 Hz until Esc (or Ctrl-C) is pressed,
+/// the program exits with 00FD or `options.frames` have run.
+///
+/// The terminal bell rings whenever the sound timer starts. F5 and F9 save
+/// and load numbered save states when `options.state_path` is set, and
+/// Backspace rewinds one second at a time.
+///
+/// With a `movie`, its recorded frames are replayed first, ignoring the
+/// keyboard; the keypad state of every frame after that is recorded into
+/// it. Rewinding and loading save states are disabled as they'd break the
+/// recording.
+pub fn run(
+    chip8: &mut Chip8,
+    options: TerminalOptions,
+    mut movie: Option<&mut Movie>,
+) -> io::Result<()> {
+    let mut out = io::stdout();
+    let raw_terminal = RawTerminal::enter(&mut out)?;
+
+    let mut slots = Slots {
+        path: options.state_path,
+        selected: 0,
+        status: String::new(),
+    };
+    let mut history = Rewind::new(options.rewind_frames);
+    let mut screen = Screen {
+        render: options.render,
+        last_render: String::new(),
+        cleared: false,
+        error: None,
+    };
+    let mut keyboard = Keyboard::new(raw_terminal.release_events);
+    let mut last_status = String::new();
+    let mut frame = 0;
+    let mut next_frame = Instant::now();
+    // Every frame is drawn from scratch after the first
+    chip8.display_dirty = true;
+
+    while options.frames.is_none_or(|frames| frame < frames) && !chip8.halted {
+        let replaying = movie
+            .as_ref()
+            .is_some_and(|movie| frame < movie.len() as u64);
+        while event::poll(Duration::ZERO)? {
+            if let Event::Key(key_event) = event::read()? {
+                if is_quit(&key_event) {
+                    return Ok(());
+                }
+                if movie.is_some() && changes_history(&key_event) {
+                    slots.status = "Unavailable while a movie is running".to_string();
+                } else if is_rewind(&key_event) {
+                    slots.status = match history.rewind(chip8, REWIND_STEP) {
+                        0 => "Nothing to rewind".to_string(),
+                        frames => format!("Rewound {} frames", frames),
+                    };
+                } else if !slots.handle_key(chip8, &key_event) && !replaying {
+                    keyboard.handle_key(&key_event);
+                }
+            }
+        }
+
+        history.push(chip8);
+        let result = match movie.as_deref_mut() {
+            Some(movie) if replaying => {
+                movie.play_frame(frame as usize, chip8);
+                chip8.run_frame_with(
+                    options.instructions_per_frame,
+                    &mut screen,
+                    &mut (),
+                    &mut Bell,
+                )
+            }
+            movie => {
+                let result = chip8.run_frame_with(
+                    options.instructions_per_frame,
+                    &mut screen,
+                    &mut keyboard,
+                    &mut Bell,
+                );
+                // Recorded even if the frame failed, so that replays reproduce the failure
+                if let Some(movie) = movie {
+                    movie.record_frame(chip8);
+                }
+                result
+            }
+        };
+        result.map_err(io::Error::other)?;
+        if let Some(e) = screen.error.take() {
+            return Err(e);
+        }
+        frame += 1;
+
+        if screen.cleared {
+            last_status.clear();
+            screen.cleared = false;
+        }
+        if slots.status != last_status {
+            queue!(
+                out,
+                cursor::MoveTo(0, screen.height()),
+                terminal::Clear(terminal::ClearType::CurrentLine),
+                style::Print(&slots.status)
+            )?;
+            last_status = slots.status.clone();
+        }
+        out.flush()?;
+
+        next_frame += FRAME_DURATION;
+        thread::sleep(next_frame.saturating_duration_since(Instant::now()));
+    }
+
+    Ok(())
+}
+
+fn is_quit(key_event: &KeyEvent) -> bool {
+    key_event.kind == KeyEventKind::Press
+        && (key_event.code == KeyCode::Esc
+            || (key_event.code == KeyCode::Char('c')
+                && key_event.modifiers.contains(KeyModifiers::CONTROL)))
+}
+
+fn is_rewind(key_event: &KeyEvent) -> bool {
+    key_event.kind != KeyEventKind::Release && key_event.code == KeyCode::Backspace
+}
+
+/// Whether the key rewinds or loads a save state.
+fn changes_history(key_event: &KeyEvent) -> bool {
+    is_rewind(key_event)
+        || (key_event.kind != KeyEventKind::Release && key_event.code == KeyCode::F(9))
+}
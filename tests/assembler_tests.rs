@@ -0,0 +1,280 @@
+use hachi::{Chip8, assemble};
+
+#[macro_use]
+mod common;
+
+use common::chip8_with_program;
+
+fn assemble_ok(source: &str) -> Vec<u8> {
+    assemble(source).unwrap_or_else(|e| panic!("Assembling should succeed: {}", e))
+}
+
+fn assert_error(source: &str, line: usize, column: usize, message: &str) {
+    let error = assemble(source).expect_err("Assembling should fail");
+
+    assert_eq!((error.line, error.column), (line, column), "{}", error);
+    assert!(
+        error.message.contains(message),
+        "'{}' should contain '{}'",
+        error.message,
+        message
+    );
+}
+
+#[test]
+fn test_assemble_instructions() {
+    let rom = assemble_ok(
+        "
+        clear return scroll-down 3 scroll-up 2 scroll-right scroll-left exit lores hires
+        v1 := 0x2A   v1 += 3   v1 -= 1   v1 := v2   v1 |= v2   v1 &= v2   v1 ^= v2
+        v1 += v2   v1 -= v2   v1 >>= v2   v1 =- v2   v1 <<= v2
+        v1 := random 0x0F   v1 := delay   v1 := key
+        delay := v1   buzzer := v1   pitch := v1
+        i := 0x300   i += v1   i := hex v1   i := bighex v1   i := long 0x1234
+        sprite v1 v2 5   bcd v1   save v1   load v1   save v1 - v2   load v2 - v1
+        saveflags v1   loadflags v1   plane 3   audio
+        jump 0x400   jump0 0x400   :call 0x400
+        ",
+    );
+
+    let opcodes: Vec<u16> = rom
+        .chunks(2)
+        .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+        .collect();
+    assert_eq!(
+        opcodes,
+        [
+            0x00E0, 0x00EE, 0x00C3, 0x00D2, 0x00FB, 0x00FC, 0x00FD, 0x00FE, 0x00FF, //
+            0x612A, 0x7103, 0x71FF, 0x8120, 0x8121, 0x8122, 0x8123, //
+            0x8124, 0x8125, 0x8126, 0x8127, 0x812E, //
+            0xC10F, 0xF107, 0xF10A, //
+            0xF115, 0xF118, 0xF13A, //
+            0xA300, 0xF11E, 0xF129, 0xF130, 0xF000, 0x1234, //
+            0xD125, 0xF133, 0xF155, 0xF165, 0x5122, 0x5213, //
+            0xF175, 0xF185, 0xF301, 0xF002, //
+            0x1400, 0xB400, 0x2400,
+        ]
+    );
+}
+
+#[test]
+fn test_assemble_labels_and_forward_references() {
+    let rom = assemble_ok(
+        "
+        : main
+            i := sprite
+            draw
+        : forever
+            jump forever
+        : draw
+            sprite v0 v0 2
+            ;
+        : sprite
+            0xFF 0b10000001
+        ",
+    );
+
+    assert_eq!(
+        rom,
+        [
+            0xA2, 0x0A, // 0x200: i := sprite
+            0x22, 0x06, // 0x202: draw
+            0x12, 0x04, // 0x204: jump forever
+            0xD0, 0x02, // 0x206: sprite v0 v0 2
+            0x00, 0xEE, // 0x208: ;
+            0xFF, 0x81, // 0x20A: sprite data
+        ]
+    );
+}
+
+#[test]
+fn test_assemble_long_forward_reference() {
+    let rom = assemble_ok("i := long data  : data 0xAA");
+
+    assert_eq!(rom, [0xF0, 0x00, 0x02, 0x04, 0xAA]);
+}
+
+#[test]
+fn test_assemble_const_and_alias() {
+    let rom = assemble_ok(
+        "
+        :const SPEED 4
+        :alias player-x v3
+        player-x += SPEED
+        :byte SPEED
+        ",
+    );
+
+    assert_eq!(rom, [0x73, 0x04, 0x04]);
+}
+
+#[test]
+fn test_assemble_if_then() {
+    let rom = assemble_ok(
+        "
+        if v1 == 5 then v2 := 1
+        if v1 != v2 then v2 := 1
+        if v1 key then v2 := 1
+        if v1 -key then v2 := 1
+        ",
+    );
+
+    assert_eq!(
+        rom,
+        [
+            0x41, 0x05, 0x62, 0x01, // SNE V1, 5
+            0x51, 0x20, 0x62, 0x01, // SE V1, V2
+            0xE1, 0xA1, 0x62, 0x01, // SKNP V1
+            0xE1, 0x9E, 0x62, 0x01, // SKP V1
+        ]
+    );
+}
+
+#[test]
+fn test_assemble_if_begin_else_end() {
+    let rom = assemble_ok(
+        "
+        if v0 == 1 begin
+            v1 := 1
+        else
+            v1 := 2
+        end
+        ",
+    );
+
+    assert_eq!(
+        rom,
+        [
+            0x30, 0x01, // 0x200: SE V0, 1
+            0x12, 0x08, // 0x202: JP else
+            0x61, 0x01, // 0x204: v1 := 1
+            0x12, 0x0A, // 0x206: JP end
+            0x61, 0x02, // 0x208: v1 := 2
+        ]
+    );
+}
+
+#[test]
+fn test_assemble_loop_while_again() {
+    let rom = assemble_ok(
+        "
+        loop
+            v0 += 1
+            while v0 != 10
+        again
+        ",
+    );
+
+    assert_eq!(
+        rom,
+        [
+            0x70, 0x01, // 0x200: v0 += 1
+            0x40, 0x0A, // 0x202: SNE V0, 10
+            0x12, 0x08, // 0x204: JP past again
+            0x12, 0x00, // 0x206: JP loop
+        ]
+    );
+}
+
+#[test]
+fn test_assemble_macros() {
+    let rom = assemble_ok(
+        "
+        :macro move register amount { register += amount }
+        :macro twice register { move register 1 move register 1 }
+        twice v2
+        ",
+    );
+
+    assert_eq!(rom, [0x72, 0x01, 0x72, 0x01]);
+}
+
+#[test]
+fn test_assemble_ignores_comments() {
+    let rom = assemble_ok("# comment\nv0 := 1 # v0 := 2\n#v0 := 3");
+
+    assert_eq!(rom, [0x60, 0x01]);
+}
+
+#[test]
+fn test_assembled_program_runs() {
+    let rom = assemble_ok(
+        "
+        : main
+            v0 := 0
+            loop
+                v0 += 1
+                while v0 != 5
+            again
+            exit
+        ",
+    );
+    let mut chip8 = chip8_with_program(Chip8::default(), &rom);
+
+    chip8.run_frame(50).unwrap();
+
+    assert!(chip8.halted, "Program should reach exit");
+    assert_eq!(chip8.registers[0x0], 5);
+}
+
+#[test]
+fn test_error_undefined_label() {
+    assert_error("v0 := 1\n  jump nowhere", 2, 8, "undefined label 'nowhere'");
+}
+
+#[test]
+fn test_error_expected_register() {
+    assert_error("sprite v0 x 1", 1, 11, "expected a register, found 'x'");
+}
+
+#[test]
+fn test_error_byte_out_of_range() {
+    assert_error("\n\n    v3 := 256", 3, 11, "out of range for a byte");
+}
+
+#[test]
+fn test_error_unknown_operator() {
+    assert_error("v1 ** v2", 1, 4, "unknown operator '**'");
+}
+
+#[test]
+fn test_error_duplicate_label() {
+    assert_error(": a\n: a", 2, 3, "'a' is already defined");
+}
+
+#[test]
+fn test_error_unterminated_blocks() {
+    assert_error(
+        "v0 := 1\nif v0 == 1 begin",
+        2,
+        1,
+        "'begin' without a matching 'end'",
+    );
+    assert_error("loop v0 += 1", 1, 1, "'loop' without a matching 'again'");
+    assert_error("again", 1, 1, "'again' without a matching 'loop'");
+}
+
+#[test]
+fn test_error_end_of_input() {
+    assert_error("v0 := 1 sprite v0", 1, 9, "found end of input");
+}
+
+#[test]
+fn test_error_recursive_macro() {
+    assert_error(":macro forever { forever }\nforever", 1, 18, "recursive");
+}
+
+#[test]
+fn test_error_loop_start_out_of_range() {
+    // 0xE00 bytes of padding put the loop at 0x1000
+    let source = format!("{}\nloop again", "0 ".repeat(0xE00));
+
+    assert_error(&source, 2, 1, "out of range for a 12-bit address");
+}
+
+#[test]
+fn test_error_program_too_large() {
+    let source = format!("{}\n: end", "0 ".repeat(0xFE00));
+
+    assert_error(&source, 2, 3, "too large to fit in memory");
+}
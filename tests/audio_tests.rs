@@ -0,0 +1,179 @@
+use hachi::frontend::Audio;
+use hachi::{Chip8, Synthesizer, assemble};
+use std::process::Command;
+
+#[macro_use]
+mod common;
+
+use common::chip8_with_source;
+
+/// Runs `frames` frames, rendering their sound at `sample_rate`.
+fn render(chip8: &mut Chip8, frames: usize, sample_rate: u32) -> Synthesizer {
+    let mut synthesizer = Synthesizer::new(sample_rate);
+    for _ in 0..frames {
+        chip8
+            .run_frame_with(4, &mut (), &mut (), &mut synthesizer)
+            .expect("Running should succeed");
+        synthesizer.render_frame();
+    }
+    synthesizer
+}
+
+/// Indices of the samples that start a run of non-silent samples.
+fn sounds(samples: &[i16]) -> Vec<usize> {
+    (0..samples.len())
+        .filter(|&i| samples[i] != 0 && (i == 0 || samples[i - 1] == 0))
+        .collect()
+}
+
+#[test]
+fn test_silent_without_sound_timer() {
+    let mut chip8 = chip8_with_source(Chip8::default(), ": loop jump loop");
+
+    let synthesizer = render(&mut chip8, 10, 6000);
+
+    assert_eq!(synthesizer.samples().len(), 1000);
+    assert!(synthesizer.samples().iter().all(|&sample| sample == 0));
+}
+
+#[test]
+fn test_beep_lasts_while_sound_timer_runs() {
+    // Beeps for 6 frames, starting on the first
+    let mut chip8 = chip8_with_source(
+        Chip8::default(),
+        ": main v0 := 6 buzzer := v0 : loop jump loop",
+    );
+
+    let synthesizer = render(&mut chip8, 20, 6000);
+
+    let samples = synthesizer.samples();
+    assert_eq!(sounds(samples), [0]);
+    assert!(samples[..600].iter().all(|&sample| sample != 0));
+    assert!(samples[600..].iter().all(|&sample| sample == 0));
+}
+
+#[test]
+fn test_beep_is_square_wave() {
+    let mut chip8 = chip8_with_source(
+        Chip8::default(),
+        ": main v0 := 60 buzzer := v0 : loop jump loop",
+    );
+
+    let synthesizer = render(&mut chip8, 60, 44_000);
+
+    let samples = synthesizer.samples();
+    // 440 Hz is 100 samples per period at 44 kHz
+    assert!(samples[..50].iter().all(|&sample| sample > 0));
+    assert!(samples[50..100].iter().all(|&sample| sample < 0));
+    let rising_edges = samples
+        .windows(2)
+        .filter(|pair| pair[0] < 0 && pair[1] > 0)
+        .count();
+    assert_eq!(rising_edges, 439);
+}
+
+#[test]
+fn test_one_frame_beep_is_heard() {
+    let mut chip8 = chip8_with_source(
+        Chip8::default(),
+        ": main v0 := 1 buzzer := v0 : loop jump loop",
+    );
+
+    let synthesizer = render(&mut chip8, 3, 6000);
+
+    let samples = synthesizer.samples();
+    assert!(samples[..100].iter().all(|&sample| sample != 0));
+    assert!(samples[100..].iter().all(|&sample| sample == 0));
+}
+
+#[test]
+fn test_xo_chip_pattern_plays_at_pitch() {
+    // Alternates 4 high and 4 low bits, at the default pitch of 4000 bits/s
+    let mut chip8 = chip8_with_source(
+        Chip8::xo_chip(),
+        ": main i := pattern audio v0 := 2 buzzer := v0 : loop jump loop
+         : pattern 0xF0 0xF0 0xF0 0xF0 0xF0 0xF0 0xF0 0xF0
+                   0xF0 0xF0 0xF0 0xF0 0xF0 0xF0 0xF0 0xF0",
+    );
+
+    let synthesizer = render(&mut chip8, 2, 8000);
+
+    // Each bit lasts 2 samples at 8 kHz
+    let samples = synthesizer.samples();
+    assert!(samples[..8].iter().all(|&sample| sample > 0));
+    assert!(samples[8..16].iter().all(|&sample| sample < 0));
+    assert!(samples[16..24].iter().all(|&sample| sample > 0));
+}
+
+#[test]
+fn test_pitch_changes_pattern_rate() {
+    let mut synthesizer = Synthesizer::new(8000);
+    let mut pattern = [0; 16];
+    pattern[0] = 0xF0;
+    // 48 above the default pitch doubles the rate to 8000 bits/s
+    synthesizer.set_pattern(&pattern, 64 + 48);
+    synthesizer.set_playing(true);
+
+    synthesizer.render_frame();
+
+    let samples = synthesizer.samples();
+    assert!(samples[..4].iter().all(|&sample| sample > 0));
+    assert!(samples[4..128].iter().all(|&sample| sample < 0));
+    assert!(samples[128..132].iter().all(|&sample| sample > 0));
+}
+
+#[test]
+fn test_wav_header() {
+    let mut synthesizer = Synthesizer::new(6000);
+    synthesizer.render_frame();
+    let mut wav = Vec::new();
+
+    synthesizer.write_wav(&mut wav).unwrap();
+
+    assert_eq!(wav.len(), 44 + 200);
+    assert_eq!(&wav[..4], b"RIFF");
+    assert_eq!(u32::from_le_bytes(wav[4..8].try_into().unwrap()), 36 + 200);
+    assert_eq!(&wav[8..16], b"WAVEfmt ");
+    assert_eq!(
+        u16::from_le_bytes([wav[20], wav[21]]),
+        1,
+        "Format should be PCM"
+    );
+    assert_eq!(u16::from_le_bytes([wav[22], wav[23]]), 1, "Should be mono");
+    assert_eq!(u32::from_le_bytes(wav[24..28].try_into().unwrap()), 6000);
+    assert_hex_equal!(
+        "bits per sample",
+        16,
+        u16::from_le_bytes([wav[34], wav[35]])
+    );
+    assert_eq!(&wav[36..40], b"data");
+    assert_eq!(u32::from_le_bytes(wav[40..44].try_into().unwrap()), 200);
+}
+
+#[test]
+fn test_cli_wav() {
+    let dir = std::env::temp_dir();
+    let rom_path = dir.join("hachi_audio_cli_test.ch8");
+    let wav_path = dir.join("hachi_audio_cli_test.wav");
+    let rom = assemble(": main v0 := 30 buzzer := v0 : loop jump loop").unwrap();
+    std::fs::write(&rom_path, rom).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_hachi"))
+        .args(["run", "--headless", "--frames", "60", "--wav"])
+        .arg(&wav_path)
+        .arg(&rom_path)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "Running should succeed");
+    let wav = std::fs::read(&wav_path).unwrap();
+    let samples: Vec<i16> = wav[44..]
+        .chunks(2)
+        .map(|bytes| i16::from_le_bytes([bytes[0], bytes[1]]))
+        .collect();
+    assert_eq!(samples.len(), 44_100);
+    assert_ne!(samples[22_049], 0, "The first half second should beep");
+    assert!(samples[22_050..].iter().all(|&sample| sample == 0));
+
+    std::fs::remove_file(wav_path).unwrap();
+}
@@ -0,0 +1,125 @@
+use hachi::{Chip8, GifRecorder, Palette, PpmSequence, assemble};
+use std::process::Command;
+
+#[macro_use]
+mod common;
+
+use common::chip8_with_source;
+
+/// Width, height and the (delay, pixels) of each frame of a GIF.
+fn decode_gif(gif: &[u8]) -> (u16, u16, Vec<(u16, Vec<u8>)>) {
+    let mut options = gif::DecodeOptions::new();
+    options.set_color_output(gif::ColorOutput::Indexed);
+    let mut decoder = options.read_info(gif).expect("Decoding should succeed");
+
+    let mut frames = Vec::new();
+    while let Some(frame) = decoder.read_next_frame().unwrap() {
+        frames.push((frame.delay, frame.buffer.to_vec()));
+    }
+    (decoder.width(), decoder.height(), frames)
+}
+
+/// Records a GIF of `frames` frames, lighting the top left pixel on the
+/// frames for which `lit` is true.
+fn record_gif(frames: usize, scale: usize, lit: impl Fn(usize) -> bool) -> Vec<u8> {
+    let mut chip8 = Chip8::default();
+    let mut recorder = GifRecorder::new(Vec::new(), scale, Palette::default()).unwrap();
+    for frame in 0..frames {
+        chip8.video[0] = lit(frame) as u8;
+        recorder.capture(&chip8).unwrap();
+    }
+    assert_eq!(recorder.frames(), frames as u64);
+    recorder.finish().expect("Finishing should succeed")
+}
+
+#[test]
+fn test_gif_merges_unchanged_frames() {
+    let gif = record_gif(60, 2, |_| true);
+
+    let (width, height, frames) = decode_gif(&gif);
+    assert_eq!((width, height), (128, 64));
+    assert_eq!(frames.len(), 1);
+    let (delay, pixels) = &frames[0];
+    assert_eq!(*delay, 100, "60 frames should last one second");
+    assert_eq!(&pixels[..3], [1, 1, 0]);
+    assert_eq!(&pixels[128..131], [1, 1, 0]);
+}
+
+#[test]
+fn test_gif_frame_delays_add_up() {
+    let gif = record_gif(30, 1, |frame| frame / 3 % 2 == 0);
+
+    let (_, _, frames) = decode_gif(&gif);
+    assert_eq!(frames.len(), 10);
+    assert!(frames.iter().all(|(delay, _)| (4..=5).contains(delay)));
+    assert_eq!(frames.iter().map(|(delay, _)| delay).sum::<u16>(), 50);
+    assert_hex_equal!("first frame", 1, frames[0].1[0]);
+    assert_hex_equal!("second frame", 0, frames[1].1[0]);
+}
+
+#[test]
+fn test_gif_drops_frames_too_short_to_show() {
+    let gif = record_gif(12, 1, |frame| frame % 2 == 0);
+
+    let (_, _, frames) = decode_gif(&gif);
+    assert!(frames.iter().all(|(delay, _)| *delay >= 2));
+    assert_eq!(frames.iter().map(|(delay, _)| delay).sum::<u16>(), 20);
+}
+
+#[test]
+fn test_gif_without_frames_fails() {
+    let recorder = GifRecorder::new(Vec::new(), 1, Palette::default()).unwrap();
+
+    assert!(recorder.finish().is_err());
+}
+
+#[test]
+fn test_ppm_sequence_writes_one_image_per_frame() {
+    let directory = std::env::temp_dir().join("hachi_ppm_sequence_test");
+    let mut chip8 = chip8_with_source(
+        Chip8::default(),
+        ": main i := hex v0 : loop sprite v0 v0 5 jump loop",
+    );
+    let mut sequence = PpmSequence::new(&directory, 1, Palette::default()).unwrap();
+
+    for _ in 0..3 {
+        chip8.run_frame(2).unwrap();
+        sequence.capture(&chip8).unwrap();
+    }
+
+    assert_eq!(sequence.frames(), 3);
+    assert_eq!(sequence.frame_path(2), directory.join("000002.ppm"));
+    let header = b"P6\n64 32\n255\n";
+    let first = std::fs::read(sequence.frame_path(0)).unwrap();
+    let second = std::fs::read(sequence.frame_path(1)).unwrap();
+    assert!(first.starts_with(header));
+    assert_hex_equal!("lit pixel", 0xFF, first[header.len()]);
+    assert_hex_equal!("cleared pixel", 0x00, second[header.len()]);
+
+    std::fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn test_cli_clip() {
+    let dir = std::env::temp_dir();
+    let rom_path = dir.join("hachi_clip_cli_test.ch8");
+    let gif_path = dir.join("hachi_clip_cli_test.gif");
+    let rom = assemble(": main i := hex v0 sprite v0 v0 5 : loop jump loop").unwrap();
+    std::fs::write(&rom_path, rom).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_hachi"))
+        .args(["run", "--headless", "--frames", "30", "--scale", "3"])
+        .arg("--clip")
+        .arg(&gif_path)
+        .arg(&rom_path)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "Running should succeed");
+    let (width, height, frames) = decode_gif(&std::fs::read(&gif_path).unwrap());
+    assert_eq!((width, height), (192, 96));
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].0, 50);
+
+    std::fs::remove_file(gif_path).unwrap();
+}
@@ -0,0 +1,38 @@
+#![allow(dead_code, unused_macros)]
+
+// Macro for hex values with configurable formatting
+macro_rules! assert_hex_equal {
+    // 8-bit hex format (default)
+    ($name:expr, $expected:expr, $actual:expr) => {
+        assert_eq!(
+            $actual, $expected,
+            "Expected {} to be 0x{:02X}, got 0x{:02X}",
+            $name, $expected, $actual
+        );
+    };
+    // 16-bit hex format
+    ($name:expr, $expected:expr, $actual:expr, 16) => {
+        assert_eq!(
+            $actual, $expected,
+            "Expected {} to be 0x{:04X}, got 0x{:04X}",
+            $name, $expected, $actual
+        );
+    };
+}
+
+use hachi::{Chip8, assemble};
+use std::io::Cursor;
+
+/// `chip8` with `program` loaded.
+pub fn chip8_with_program(mut chip8: Chip8, program: &[u8]) -> Chip8 {
+    chip8
+        .load_rom_from_reader(Cursor::new(program.to_vec()))
+        .expect("Loading ROM should succeed");
+    chip8
+}
+
+/// `chip8` with the Octo `source` assembled and loaded.
+pub fn chip8_with_source(chip8: Chip8, source: &str) -> Chip8 {
+    let rom = assemble(source).expect("Assembling should succeed");
+    chip8_with_program(chip8, &rom)
+}
@@ -0,0 +1,130 @@
+use hachi::Chip8;
+
+#[macro_use]
+mod common;
+
+use common::chip8_with_program;
+
+#[test]
+fn test_cycle_fetches_opcode_and_advances_pc() {
+    let mut chip8 = chip8_with_program(Chip8::default(), &[0x6A, 0x55]); // LD VA, 0x55
+
+    chip8.cycle().unwrap();
+
+    assert_hex_equal!("opcode", 0x6A55, chip8.opcode, 16);
+    assert_hex_equal!("program counter", 0x202, chip8.pc, 16);
+    assert_hex_equal!("register A", 0x55, chip8.registers[0xA]);
+}
+
+#[test]
+fn test_cycle_jump() {
+    let mut chip8 = chip8_with_program(Chip8::default(), &[0x12, 0x40]); // JP 0x240
+
+    chip8.cycle().unwrap();
+
+    assert_hex_equal!("program counter", 0x240, chip8.pc, 16);
+}
+
+#[test]
+fn test_cycle_call_pushes_return_address() {
+    let mut chip8 = chip8_with_program(
+        Chip8::default(),
+        &[
+            0x22, 0x04, // 0x200: CALL 0x204
+            0x00, 0x00, // 0x202: (return lands here)
+            0x00, 0xEE, // 0x204: RET
+        ],
+    );
+
+    chip8.cycle().unwrap();
+
+    assert_hex_equal!("program counter", 0x204, chip8.pc, 16);
+    assert_hex_equal!("stack pointer", 1, chip8.sp);
+    assert_hex_equal!("stack[0]", 0x202, chip8.stack[0], 16);
+
+    chip8.cycle().unwrap();
+
+    assert_hex_equal!("program counter", 0x202, chip8.pc, 16);
+    assert_hex_equal!("stack pointer", 0, chip8.sp);
+}
+
+#[test]
+fn test_cycle_skip_jumps_over_next_instruction() {
+    let mut chip8 = chip8_with_program(
+        Chip8::default(),
+        &[
+            0x30, 0x00, // SE V0, 0x00
+            0x61, 0x01, // LD V1, 0x01 (skipped)
+            0x62, 0x02, // LD V2, 0x02
+        ],
+    );
+
+    chip8.cycle().unwrap();
+    chip8.cycle().unwrap();
+
+    assert_hex_equal!("program counter", 0x206, chip8.pc, 16);
+    assert_hex_equal!("register 1", 0x00, chip8.registers[0x1]);
+    assert_hex_equal!("register 2", 0x02, chip8.registers[0x2]);
+}
+
+#[test]
+fn test_cycle_runs_arithmetic_program() {
+    let mut chip8 = chip8_with_program(
+        Chip8::default(),
+        &[
+            0x60, 0xF0, // LD V0, 0xF0
+            0x61, 0x20, // LD V1, 0x20
+            0x80, 0x14, // ADD V0, V1
+            0xA3, 0x00, // LD I, 0x300
+        ],
+    );
+
+    for _ in 0..4 {
+        chip8.cycle().unwrap();
+    }
+
+    assert_hex_equal!("register 0", 0x10, chip8.registers[0x0]);
+    assert_hex_equal!("register F", 1, chip8.registers[0xF]);
+    assert_hex_equal!("index", 0x300, chip8.index, 16);
+    assert_hex_equal!("program counter", 0x208, chip8.pc, 16);
+}
+
+#[test]
+fn test_cycle_draws_font_sprite() {
+    let mut chip8 = chip8_with_program(
+        Chip8::default(),
+        &[
+            0xA0, 0x50, // LD I, 0x050 (font glyph "0")
+            0xD0, 0x05, // DRW V0, V0, 5
+        ],
+    );
+
+    chip8.cycle().unwrap();
+    chip8.cycle().unwrap();
+
+    // Top row of "0" is 0xF0: four lit pixels followed by four dark ones
+    assert_eq!(chip8.video[0..8], [1, 1, 1, 1, 0, 0, 0, 0]);
+    assert_hex_equal!("register F", 0, chip8.registers[0xF]);
+}
+
+#[test]
+fn test_step_ticks_timers_once_per_frame() {
+    let mut chip8 = chip8_with_program(
+        Chip8::default(),
+        &[
+            0x60, 0x0A, // LD V0, 0x0A
+            0xF0, 0x15, // LD DT, V0
+            0x12, 0x04, // JP 0x204
+        ],
+    );
+
+    for _ in 0..2 {
+        chip8.step(3).unwrap();
+    }
+    assert_hex_equal!("delay timer", 10, chip8.delay_timer);
+    assert_eq!(chip8.frame_cycles, 2);
+
+    chip8.step(3).unwrap();
+    assert_hex_equal!("delay timer", 9, chip8.delay_timer);
+    assert_eq!(chip8.frame_cycles, 0);
+}
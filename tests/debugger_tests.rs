@@ -0,0 +1,235 @@
+use hachi::{Chip8, Debugger};
+use std::io::Cursor;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[macro_use]
+mod common;
+
+use common::chip8_with_source;
+
+const PROGRAM: &str = "
+    : main
+        v0 := 1
+        draw
+        i := 0x300
+        v1 := 0xAB
+        save v1
+    : forever
+        jump forever
+    : draw
+        i := hex v0
+        sprite v0 v0 5
+        return
+";
+
+fn debugger_with_program(source: &str) -> Debugger {
+    Debugger::new(chip8_with_source(Chip8::default(), source))
+}
+
+#[test]
+fn test_step() {
+    let mut debugger = debugger_with_program(PROGRAM);
+
+    let output = debugger.command("step");
+    assert_eq!(output, "0x0202: 220C      CALL 0x20C\n");
+
+    debugger.command("step 2");
+    assert_hex_equal!("program counter", 0x20E, debugger.chip8.pc, 16);
+}
+
+#[test]
+fn test_empty_line_repeats_last_command() {
+    let mut debugger = debugger_with_program(PROGRAM);
+
+    debugger.command("step");
+    debugger.command("");
+
+    assert_hex_equal!("program counter", 0x20C, debugger.chip8.pc, 16);
+}
+
+#[test]
+fn test_next_steps_over_calls() {
+    let mut debugger = debugger_with_program(PROGRAM);
+    debugger.command("step");
+
+    let output = debugger.command("next");
+
+    assert_eq!(output, "0x0204: A300      LD I, 0x300\n");
+    assert_hex_equal!("stack pointer", 0, debugger.chip8.sp);
+    assert_ne!(debugger.chip8.video[64 + 3], 0, "Sprite should be drawn");
+}
+
+#[test]
+fn test_continue_stops_at_address_breakpoint() {
+    let mut debugger = debugger_with_program(PROGRAM);
+
+    assert_eq!(debugger.command("break 210"), "1 breakpoint at 0x0210\n");
+    let output = debugger.command("continue");
+
+    assert!(output.starts_with("Breakpoint 1\n0x0210"), "{}", output);
+    assert_hex_equal!("stack pointer", 1, debugger.chip8.sp);
+}
+
+#[test]
+fn test_continue_stops_at_opcode_breakpoint() {
+    let mut debugger = debugger_with_program(PROGRAM);
+
+    assert_eq!(
+        debugger.command("break op D???"),
+        "1 breakpoint on opcode D???\n"
+    );
+    let output = debugger.command("c");
+
+    assert!(output.contains("DRW V0, V0, 5"), "{}", output);
+}
+
+#[test]
+fn test_continue_stops_at_watchpoint() {
+    let mut debugger = debugger_with_program(PROGRAM);
+
+    debugger.command("watch 301 1");
+    let output = debugger.command("continue");
+
+    assert!(
+        output.starts_with("Watchpoint 1: 0x0301 changed from 0x00 to 0xAB\n"),
+        "{}",
+        output
+    );
+    assert_hex_equal!("program counter", 0x20A, debugger.chip8.pc, 16);
+}
+
+#[test]
+fn test_continue_stops_when_interrupted() {
+    // A loop longer than one instruction never stops by itself
+    let mut debugger = debugger_with_program(": main v0 := 1 jump main");
+    // An interrupt from before continuing is ignored
+    debugger.interrupt.store(true, Ordering::Relaxed);
+    let interrupt = Arc::clone(&debugger.interrupt);
+    let interrupter = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        interrupt.store(true, Ordering::Relaxed);
+    });
+
+    let start = Instant::now();
+    let output = debugger.command("continue");
+    interrupter.join().unwrap();
+
+    assert!(start.elapsed() >= Duration::from_millis(50));
+    assert!(output.starts_with("Interrupted\n0x020"), "{}", output);
+    assert!(!debugger.interrupt.load(Ordering::Relaxed));
+}
+
+#[test]
+fn test_continue_stops_at_infinite_loop() {
+    let mut debugger = debugger_with_program(PROGRAM);
+
+    let output = debugger.command("continue");
+
+    assert!(output.starts_with("Infinite loop at 0x020A"), "{}", output);
+}
+
+#[test]
+fn test_continue_stops_on_error() {
+    let mut debugger = debugger_with_program("return");
+
+    let output = debugger.command("continue");
+
+    assert!(output.starts_with("Error: stack underflow"), "{}", output);
+}
+
+#[test]
+fn test_delete_breakpoint() {
+    let mut debugger = debugger_with_program(PROGRAM);
+    debugger.command("break 210");
+
+    assert_eq!(debugger.command("delete 1"), "Deleted 1\n");
+    assert_eq!(debugger.command("list"), "No breakpoints or watchpoints\n");
+    assert!(debugger.command("continue").starts_with("Infinite loop"));
+}
+
+#[test]
+fn test_registers_and_stack() {
+    let mut debugger = debugger_with_program(PROGRAM);
+    debugger.command("step 2");
+
+    let registers = debugger.command("regs");
+    assert!(registers.starts_with("V0=0x01 V1=0x00"), "{}", registers);
+    assert!(registers.contains("PC=0x020C SP=1"), "{}", registers);
+
+    assert_eq!(debugger.command("stack"), "#0 0x0204\n");
+}
+
+#[test]
+fn test_hexdump() {
+    let mut debugger = debugger_with_program(PROGRAM);
+
+    let output = debugger.command("mem 200 4");
+
+    assert_eq!(output, format!("0x0200: 60 01 22 0C{:38}|`.\".|\n", ""));
+}
+
+#[test]
+fn test_huge_lengths_stop_at_end_of_memory() {
+    let mut debugger = debugger_with_program(PROGRAM);
+
+    let output = debugger.command("mem FF0 18446744073709551615");
+    debugger.command("watch 1 18446744073709551615");
+    debugger.command("step");
+
+    assert_eq!(output.lines().count(), 1);
+    assert!(output.starts_with("0x0FF0: "), "{}", output);
+}
+
+#[test]
+fn test_disasm_marks_pc() {
+    let mut debugger = debugger_with_program(PROGRAM);
+
+    let output = debugger.command("disasm 200 2");
+
+    assert_eq!(
+        output,
+        "=> 0x0200: 6001      LD V0, 0x01\n   0x0202: 220C      CALL 0x20C\n"
+    );
+}
+
+#[test]
+fn test_key_commands() {
+    let mut debugger = debugger_with_program("v1 := key");
+
+    let output = debugger.command("continue");
+    assert!(output.starts_with("Waiting for a key"), "{}", output);
+
+    debugger.command("press a");
+    debugger.command("release a");
+
+    assert_hex_equal!("register 1", 0xA, debugger.chip8.registers[0x1]);
+}
+
+#[test]
+fn test_run_reads_commands_until_quit() {
+    let mut debugger = debugger_with_program(PROGRAM);
+    let mut output = Vec::new();
+
+    debugger
+        .run(Cursor::new("step\nquit\nstep\n"), &mut output)
+        .unwrap();
+
+    let output = String::from_utf8(output).unwrap();
+    assert_eq!(
+        output,
+        "0x0200: 6001      LD V0, 0x01\n(hachi) 0x0202: 220C      CALL 0x20C\n(hachi) "
+    );
+}
+
+#[test]
+fn test_unknown_command() {
+    let mut debugger = debugger_with_program(PROGRAM);
+
+    assert_eq!(
+        debugger.command("frobnicate"),
+        "Unknown command: frobnicate (try 'help')\n"
+    );
+}
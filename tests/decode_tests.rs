@@ -0,0 +1,114 @@
+use hachi::{Chip8, DecodeError, Instruction, decode};
+
+#[macro_use]
+mod common;
+
+#[test]
+fn test_decode_all_instructions() {
+    let cases = [
+        (0x00C5, Instruction::ScrollDown { n: 5 }),
+        (0x00D5, Instruction::ScrollUp { n: 5 }),
+        (0x00E0, Instruction::ClearDisplay),
+        (0x00EE, Instruction::Return),
+        (0x00FB, Instruction::ScrollRight),
+        (0x00FC, Instruction::ScrollLeft),
+        (0x00FD, Instruction::Exit),
+        (0x00FE, Instruction::LowRes),
+        (0x00FF, Instruction::HighRes),
+        (0x1A59, Instruction::Jump { address: 0xA59 }),
+        (0x2A59, Instruction::Call { address: 0xA59 }),
+        (0x3A12, Instruction::SkipEqualByte { x: 0xA, byte: 0x12 }),
+        (0x4A12, Instruction::SkipNotEqualByte { x: 0xA, byte: 0x12 }),
+        (0x5AB0, Instruction::SkipEqual { x: 0xA, y: 0xB }),
+        (0x5AB2, Instruction::StoreRange { x: 0xA, y: 0xB }),
+        (0x5AB3, Instruction::LoadRange { x: 0xA, y: 0xB }),
+        (0x6A12, Instruction::LoadByte { x: 0xA, byte: 0x12 }),
+        (0x7A12, Instruction::AddByte { x: 0xA, byte: 0x12 }),
+        (0x8AB0, Instruction::Load { x: 0xA, y: 0xB }),
+        (0x8AB1, Instruction::Or { x: 0xA, y: 0xB }),
+        (0x8AB2, Instruction::And { x: 0xA, y: 0xB }),
+        (0x8AB3, Instruction::Xor { x: 0xA, y: 0xB }),
+        (0x8AB4, Instruction::Add { x: 0xA, y: 0xB }),
+        (0x8AB5, Instruction::Sub { x: 0xA, y: 0xB }),
+        (0x8AB6, Instruction::ShiftRight { x: 0xA, y: 0xB }),
+        (0x8AB7, Instruction::SubN { x: 0xA, y: 0xB }),
+        (0x8ABE, Instruction::ShiftLeft { x: 0xA, y: 0xB }),
+        (0x9AB0, Instruction::SkipNotEqual { x: 0xA, y: 0xB }),
+        (0xA4E2, Instruction::LoadIndex { address: 0x4E2 }),
+        (0xB4E2, Instruction::JumpV0 { address: 0x4E2 }),
+        (0xC5F0, Instruction::Random { x: 0x5, byte: 0xF0 }),
+        (
+            0xD125,
+            Instruction::DrawSprite {
+                x: 0x1,
+                y: 0x2,
+                n: 5,
+            },
+        ),
+        (0xE19E, Instruction::SkipKeyPressed { x: 0x1 }),
+        (0xE1A1, Instruction::SkipKeyNotPressed { x: 0x1 }),
+        (0xF000, Instruction::LoadLongIndex),
+        (0xF201, Instruction::SelectPlanes { planes: 0x2 }),
+        (0xF002, Instruction::LoadAudioPattern),
+        (0xF307, Instruction::LoadDelayTimer { x: 0x3 }),
+        (0xF30A, Instruction::WaitKey { x: 0x3 }),
+        (0xF315, Instruction::SetDelayTimer { x: 0x3 }),
+        (0xF318, Instruction::SetSoundTimer { x: 0x3 }),
+        (0xF31E, Instruction::AddIndex { x: 0x3 }),
+        (0xF329, Instruction::LoadFont { x: 0x3 }),
+        (0xF330, Instruction::LoadBigFont { x: 0x3 }),
+        (0xF33A, Instruction::SetPitch { x: 0x3 }),
+        (0xF333, Instruction::StoreBcd { x: 0x3 }),
+        (0xF355, Instruction::StoreRegisters { x: 0x3 }),
+        (0xF365, Instruction::LoadRegisters { x: 0x3 }),
+        (0xF375, Instruction::StoreFlags { x: 0x3 }),
+        (0xF385, Instruction::LoadFlags { x: 0x3 }),
+    ];
+
+    for (opcode, expected) in cases {
+        assert_eq!(
+            decode(opcode),
+            Ok(expected),
+            "Opcode 0x{:04X} should decode to {:?}",
+            opcode,
+            expected
+        );
+    }
+}
+
+#[test]
+fn test_decode_unknown_opcodes() {
+    let unknown = [
+        0x0123, 0x5AB1, 0x5AB4, 0x8AB8, 0x8ABF, 0x9AB1, 0xE19F, 0xF3FF, 0xF300, 0xF302,
+    ];
+
+    for opcode in unknown {
+        assert_eq!(
+            decode(opcode),
+            Err(DecodeError { opcode }),
+            "Opcode 0x{:04X} should not decode",
+            opcode
+        );
+    }
+}
+
+#[test]
+fn test_decode_error_message() {
+    let error = decode(0x5AB1).unwrap_err();
+
+    assert_eq!(error.to_string(), "unknown opcode 0x5AB1");
+}
+
+#[test]
+fn test_execute_decoded_instruction() {
+    let mut chip8 = Chip8::default();
+
+    chip8
+        .execute(Instruction::LoadByte { x: 0xA, byte: 0x55 })
+        .unwrap();
+    chip8
+        .execute(Instruction::AddByte { x: 0xA, byte: 0x01 })
+        .unwrap();
+
+    assert_hex_equal!("register A", 0x56, chip8.registers[0xA]);
+}
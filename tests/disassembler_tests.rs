@@ -0,0 +1,155 @@
+use hachi::{Chip8, Instruction, Syntax, disassemble};
+use std::process::Command;
+
+#[macro_use]
+mod common;
+
+use common::chip8_with_program;
+
+// Calls a subroutine drawing a sprite, then loops forever
+const PROGRAM: [u8; 16] = [
+    0x22, 0x06, // 0x200: CALL 0x206
+    0x12, 0x04, // 0x202: JP 0x204
+    0x12, 0x04, // 0x204: JP 0x204
+    0xA2, 0x0E, // 0x206: LD I, 0x20E
+    0x30, 0x00, // 0x208: SE V0, 0x00
+    0xD0, 0x01, // 0x20A: DRW V0, V0, 1
+    0x00, 0xEE, // 0x20C: RET
+    0xFF, 0x81, // 0x20E: sprite data
+];
+
+#[test]
+fn test_disassemble_separates_code_from_data() {
+    let chip8 = chip8_with_program(Chip8::default(), &PROGRAM);
+
+    let disassembly = disassemble(&chip8.memory, 0x200, PROGRAM.len());
+
+    let addresses: Vec<u16> = disassembly.lines.iter().map(|line| line.address).collect();
+    assert_eq!(
+        addresses,
+        [0x200, 0x202, 0x204, 0x206, 0x208, 0x20A, 0x20C, 0x20E]
+    );
+    assert_eq!(
+        disassembly.lines[0].instruction,
+        Some(Instruction::Call { address: 0x206 })
+    );
+    assert_eq!(disassembly.lines[7].instruction, None);
+    assert_eq!(disassembly.lines[7].bytes, [0xFF, 0x81]);
+}
+
+#[test]
+fn test_disassemble_labels_targets() {
+    let chip8 = chip8_with_program(Chip8::default(), &PROGRAM);
+
+    let disassembly = disassemble(&chip8.memory, 0x200, PROGRAM.len());
+
+    let labels: Vec<(u16, &str)> = disassembly
+        .labels
+        .iter()
+        .map(|(&address, label)| (address, label.as_str()))
+        .collect();
+    assert_eq!(
+        labels,
+        [
+            (0x200, "main"),
+            (0x204, "label_204"),
+            (0x206, "sub_206"),
+            (0x20E, "data_20E"),
+        ]
+    );
+}
+
+#[test]
+fn test_disassemble_follows_skips() {
+    let program = [
+        0x30, 0x00, // 0x200: SE V0, 0x00
+        0x00, 0xFD, // 0x202: EXIT
+        0x00, 0xE0, // 0x204: CLS
+        0x00, 0xFD, // 0x206: EXIT
+        0x00, 0xE0, // 0x208: data
+    ];
+    let chip8 = chip8_with_program(Chip8::default(), &program);
+
+    let disassembly = disassemble(&chip8.memory, 0x200, program.len());
+
+    let code = disassembly
+        .lines
+        .iter()
+        .filter(|line| line.instruction.is_some())
+        .count();
+    assert_eq!(code, 4);
+    assert_eq!(disassembly.lines[4].instruction, None);
+}
+
+#[test]
+fn test_disassemble_long_instruction() {
+    let program = [
+        0xF0, 0x00, 0x02, 0x06, // 0x200: LD I, long 0x206
+        0x12, 0x04, // 0x204: JP 0x204
+        0xAA, // 0x206: data
+    ];
+    let chip8 = chip8_with_program(Chip8::xo_chip(), &program);
+
+    let disassembly = disassemble(&chip8.memory, 0x200, program.len());
+
+    assert_eq!(disassembly.lines[0].bytes, [0xF0, 0x00, 0x02, 0x06]);
+    assert_eq!(disassembly.lines[1].address, 0x204);
+    assert!(
+        disassembly
+            .listing(Syntax::Octo)
+            .contains("i := long 0x206")
+    );
+}
+
+#[test]
+fn test_listing_cowgod() {
+    let chip8 = chip8_with_program(Chip8::default(), &PROGRAM);
+
+    let listing = disassemble(&chip8.memory, 0x200, PROGRAM.len()).listing(Syntax::Cowgod);
+    let lines: Vec<&str> = listing.lines().collect();
+
+    assert_eq!(lines[0], "main:");
+    assert_eq!(
+        lines[1].split_whitespace().collect::<Vec<_>>(),
+        ["0200", "22", "06", "CALL", "sub_206"]
+    );
+    assert!(listing.contains("sub_206:\n0206  A2 0E"));
+    assert!(listing.contains("LD I, data_20E"));
+    assert!(listing.contains("SE V0, 0x00"));
+    assert!(listing.contains("DRW V0, V0, 1"));
+    assert!(listing.contains("DB 0xFF, 0x81"));
+}
+
+#[test]
+fn test_listing_octo() {
+    let chip8 = chip8_with_program(Chip8::default(), &PROGRAM);
+
+    let listing = disassemble(&chip8.memory, 0x200, PROGRAM.len()).listing(Syntax::Octo);
+
+    assert!(listing.starts_with(": main\n"));
+    assert!(listing.contains(":call sub_206"));
+    assert!(listing.contains(": label_204\n0204  12 04"));
+    assert!(listing.contains("jump label_204"));
+    assert!(listing.contains("i := data_20E"));
+    assert!(listing.contains("if v0 != 0x00 then"));
+    assert!(listing.contains("sprite v0 v0 1"));
+    assert!(listing.contains("return"));
+    assert!(listing.contains("0xFF 0x81"));
+}
+
+#[test]
+fn test_disasm_command() {
+    let rom_filepath = std::env::temp_dir().join("hachi_disasm_test.ch8");
+    std::fs::write(&rom_filepath, PROGRAM).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_hachi"))
+        .args(["disasm", "--syntax", "octo"])
+        .arg(&rom_filepath)
+        .output()
+        .expect("hachi should run");
+
+    assert!(output.status.success(), "hachi should exit successfully");
+    let listing = String::from_utf8(output.stdout).unwrap();
+    assert!(listing.starts_with(": main\n0200  22 06"));
+    assert_eq!(listing.lines().count(), 12);
+}
@@ -0,0 +1,162 @@
+use hachi::{Chip8, Chip8Error, Chip8ErrorKind};
+
+#[macro_use]
+mod common;
+
+use common::chip8_with_program;
+
+#[test]
+fn test_cycle_invalid_opcode() {
+    let mut chip8 = chip8_with_program(Chip8::default(), &[0x5A, 0xB1]); // not a valid 5xy_ instruction
+
+    let error = chip8.cycle().unwrap_err();
+
+    assert_eq!(
+        error,
+        Chip8Error {
+            kind: Chip8ErrorKind::InvalidOpcode,
+            pc: 0x200,
+            opcode: 0x5AB1,
+        }
+    );
+    // Program counter stays on the faulting instruction
+    assert_hex_equal!("program counter", 0x200, chip8.pc, 16);
+}
+
+#[test]
+fn test_cycle_stack_underflow() {
+    let mut chip8 = chip8_with_program(Chip8::default(), &[0x00, 0xEE]); // RET
+
+    let error = chip8.cycle().unwrap_err();
+
+    assert_eq!(error.kind, Chip8ErrorKind::StackUnderflow);
+    assert_hex_equal!("error pc", 0x200, error.pc, 16);
+    assert_hex_equal!("error opcode", 0x00EE, error.opcode, 16);
+    assert_hex_equal!("stack pointer", 0, chip8.sp);
+}
+
+#[test]
+fn test_cycle_stack_overflow() {
+    let mut chip8 = chip8_with_program(Chip8::default(), &[0x22, 0x00]); // CALL 0x200 (recurses forever)
+
+    for _ in 0..16 {
+        chip8.cycle().unwrap();
+    }
+    let error = chip8.cycle().unwrap_err();
+
+    assert_eq!(error.kind, Chip8ErrorKind::StackOverflow);
+    assert_hex_equal!("stack pointer", 16, chip8.sp);
+}
+
+#[test]
+fn test_cycle_fetch_out_of_bounds() {
+    let mut chip8 = Chip8 {
+        pc: 0xFFF,
+        ..Default::default()
+    };
+
+    let error = chip8.cycle().unwrap_err();
+
+    assert_eq!(
+        error.kind,
+        Chip8ErrorKind::MemoryOutOfBounds { address: 0x1000 }
+    );
+    assert_hex_equal!("error pc", 0xFFF, error.pc, 16);
+}
+
+#[test]
+fn test_jump_v0_out_of_bounds() {
+    let mut chip8 = Chip8 {
+        registers: {
+            let mut registers = [0; 16];
+            registers[0] = 0xFF;
+            registers
+        },
+        ..Default::default()
+    };
+
+    let result = chip8.jump_v0(0xFFF);
+
+    assert_eq!(
+        result,
+        Err(Chip8ErrorKind::MemoryOutOfBounds { address: 0x10FE })
+    );
+    assert_hex_equal!("program counter", 0x200, chip8.pc, 16);
+}
+
+#[test]
+fn test_draw_out_of_bounds() {
+    let mut chip8 = Chip8 {
+        index: 0xFFE,
+        ..Default::default()
+    };
+
+    let result = chip8.draw_vx_vy_n(0x0, 0x0, 5);
+
+    assert_eq!(
+        result,
+        Err(Chip8ErrorKind::MemoryOutOfBounds { address: 0x1000 })
+    );
+    assert_eq!(chip8.video, [0; 128 * 64], "Nothing should be drawn");
+}
+
+#[test]
+fn test_load_bcd_out_of_bounds() {
+    let mut chip8 = Chip8 {
+        index: 0xFFE,
+        ..Default::default()
+    };
+
+    let result = chip8.load_bcd_vx(0x0);
+
+    assert_eq!(
+        result,
+        Err(Chip8ErrorKind::MemoryOutOfBounds { address: 0x1000 })
+    );
+}
+
+#[test]
+fn test_store_registers_out_of_bounds() {
+    let mut chip8 = Chip8 {
+        index: 0xFFA,
+        ..Default::default()
+    };
+    let initial_memory = chip8.memory.clone();
+
+    let result = chip8.store_registers(0xF);
+
+    assert_eq!(
+        result,
+        Err(Chip8ErrorKind::MemoryOutOfBounds { address: 0x1000 })
+    );
+    assert_eq!(chip8.memory, initial_memory, "Memory should not change");
+}
+
+#[test]
+fn test_load_registers_out_of_bounds() {
+    let mut chip8 = Chip8 {
+        index: 0xFFA,
+        ..Default::default()
+    };
+
+    let result = chip8.load_registers(0xF);
+
+    assert_eq!(
+        result,
+        Err(Chip8ErrorKind::MemoryOutOfBounds { address: 0x1000 })
+    );
+}
+
+#[test]
+fn test_error_message() {
+    let error = Chip8Error {
+        kind: Chip8ErrorKind::StackUnderflow,
+        pc: 0x2A4,
+        opcode: 0x00EE,
+    };
+
+    assert_eq!(
+        error.to_string(),
+        "stack underflow at 0x2A4 (opcode 0x00EE)"
+    );
+}
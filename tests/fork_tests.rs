@@ -0,0 +1,115 @@
+use hachi::Chip8;
+use std::thread;
+
+#[macro_use]
+mod common;
+
+use common::chip8_with_source;
+
+// Counts the frames a key is held in v1, mixing in random bytes in v2
+const PROGRAM: &str = "
+    : loop
+        v0 := 7
+        if v0 key then v1 += 1
+        v3 := random 0xFF
+        v2 += v3
+        v4 := 1
+        delay := v4
+    : wait
+        v4 := delay
+        if v4 != 0 then jump wait
+        jump loop
+";
+
+fn seeded_chip8() -> Chip8 {
+    let mut chip8 = Chip8::default();
+    chip8.seed_rng(5);
+    chip8_with_source(chip8, PROGRAM)
+}
+
+#[test]
+fn test_chip8_is_clone_send_and_sync() {
+    fn assert_traits<T: Clone + Send + Sync>() {}
+
+    assert_traits::<Chip8>();
+}
+
+#[test]
+fn test_fork_continues_identically() {
+    let mut chip8 = seeded_chip8();
+    chip8.run_frame(30).unwrap();
+
+    let mut fork = chip8.fork();
+    chip8.run_frame(50).unwrap();
+    fork.run_frame(50).unwrap();
+
+    assert_eq!(fork.registers, chip8.registers);
+    assert_hex_equal!("program counter", chip8.pc, fork.pc, 16);
+}
+
+#[test]
+fn test_fork_is_independent() {
+    let chip8 = seeded_chip8();
+    let mut fork = chip8.fork();
+
+    fork.press_key(0x7);
+    fork.memory[0x300] = 0xFF;
+    fork.run_frame(20).unwrap();
+
+    assert!(!chip8.keypad[0x7]);
+    assert_hex_equal!("memory 0x300", 0x00, chip8.memory[0x300]);
+    assert_hex_equal!("program counter", 0x200, chip8.pc, 16);
+}
+
+#[test]
+fn test_reseeded_forks_diverge() {
+    let chip8 = seeded_chip8();
+    let mut fork = chip8.fork();
+    let mut reseeded = chip8.fork();
+    reseeded.seed_rng(6);
+
+    fork.run_frame(100).unwrap();
+    reseeded.run_frame(100).unwrap();
+
+    assert_ne!(fork.registers[0x2], reseeded.registers[0x2]);
+}
+
+/// Holds key 7 for `frames` frames and returns the count in v1.
+fn hold_key(mut chip8: Chip8, frames: usize) -> u8 {
+    chip8.press_key(0x7);
+    for _ in 0..frames {
+        chip8.run_frame(10).unwrap();
+    }
+    chip8.release_key(0x7);
+    chip8.run_frame(10).unwrap();
+    chip8.registers[0x1]
+}
+
+#[test]
+fn test_forks_run_in_parallel() {
+    let mut chip8 = seeded_chip8();
+    chip8.run_frame(10).unwrap();
+
+    let parallel: Vec<u8> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..16)
+            .map(|frames| {
+                let fork = chip8.fork();
+                scope.spawn(move || hold_key(fork, frames))
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    });
+    let serial: Vec<u8> = (0..16)
+        .map(|frames| hold_key(chip8.fork(), frames))
+        .collect();
+
+    assert_eq!(parallel, serial);
+    assert!(
+        parallel[15] > parallel[0],
+        "Holding longer should count more"
+    );
+    assert_hex_equal!("original register 1", 0, chip8.registers[0x1]);
+}
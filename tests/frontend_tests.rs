@@ -0,0 +1,174 @@
+use hachi::Chip8;
+use hachi::frontend::{Audio, Display, Input};
+
+#[macro_use]
+mod common;
+
+use common::chip8_with_source;
+
+/// Records what the emulator reported.
+#[derive(Default)]
+struct Recorder {
+    presented: Vec<String>,
+    playing: Vec<bool>,
+    patterns: Vec<([u8; 16], u8)>,
+}
+
+impl Display for Recorder {
+    fn present(&mut self, chip8: &Chip8) {
+        self.presented.push(chip8.video_ascii());
+    }
+}
+
+impl Audio for Recorder {
+    fn set_playing(&mut self, playing: bool) {
+        self.playing.push(playing);
+    }
+
+    fn set_pattern(&mut self, pattern: &[u8; 16], pitch: u8) {
+        self.patterns.push((*pattern, pitch));
+    }
+}
+
+/// Replays a fixed keypad state per frame.
+struct Keys(Vec<Option<[bool; 16]>>);
+
+impl Input for Keys {
+    fn keypad(&mut self) -> Option<[bool; 16]> {
+        self.0.remove(0)
+    }
+}
+
+fn run_frames(chip8: &mut Chip8, frames: usize, recorder: &mut Recorder) {
+    for _ in 0..frames {
+        chip8
+            .run_frame_with(1, &mut (), &mut (), recorder)
+            .expect("Running should succeed");
+    }
+}
+
+#[test]
+fn test_display_presented_only_after_changes() {
+    // Draws once, then waits
+    let mut chip8 = chip8_with_source(
+        Chip8::default(),
+        ": main i := hex v0 sprite v0 v0 5 : loop jump loop",
+    );
+    let mut display = Recorder::default();
+
+    for _ in 0..6 {
+        chip8
+            .run_frame_with(1, &mut display, &mut (), &mut ())
+            .unwrap();
+    }
+
+    // The first frame, then the frame of the sprite
+    assert_eq!(display.presented.len(), 2);
+    assert_eq!(display.presented[1], chip8.video_ascii());
+    assert!(!chip8.display_dirty);
+}
+
+#[test]
+fn test_display_presented_after_load_state() {
+    let mut chip8 = chip8_with_source(Chip8::default(), ": loop jump loop");
+    let mut display = Recorder::default();
+    chip8
+        .run_frame_with(1, &mut display, &mut (), &mut ())
+        .unwrap();
+    let mut state = Vec::new();
+    chip8.save_state(&mut state).unwrap();
+
+    chip8.load_state(state.as_slice()).unwrap();
+    chip8
+        .run_frame_with(1, &mut display, &mut (), &mut ())
+        .unwrap();
+
+    assert_eq!(display.presented.len(), 2);
+}
+
+#[test]
+fn test_input_sets_keypad() {
+    let mut chip8 = chip8_with_source(Chip8::default(), ": main v3 := key : loop jump loop");
+    let mut held = [false; 16];
+    held[0x7] = true;
+    let mut input = Keys(vec![None, Some(held), None, Some([false; 16])]);
+
+    chip8
+        .run_frame_with(1, &mut (), &mut input, &mut ())
+        .unwrap();
+    chip8
+        .run_frame_with(1, &mut (), &mut input, &mut ())
+        .unwrap();
+    assert!(chip8.keypad[0x7], "Key 7 should be held");
+
+    // None leaves the key held
+    chip8
+        .run_frame_with(1, &mut (), &mut input, &mut ())
+        .unwrap();
+    assert!(chip8.keypad[0x7], "Key 7 should still be held");
+
+    chip8
+        .run_frame_with(1, &mut (), &mut input, &mut ())
+        .unwrap();
+    assert!(!chip8.keypad[0x7], "Key 7 should be released");
+    chip8.cycle().unwrap();
+    assert_hex_equal!("register 3", 0x7, chip8.registers[0x3]);
+}
+
+#[test]
+fn test_audio_playing_follows_sound_timer() {
+    let mut chip8 = chip8_with_source(
+        Chip8::default(),
+        ": main v0 := 3 buzzer := v0 : loop jump loop",
+    );
+    let mut audio = Recorder::default();
+
+    run_frames(&mut chip8, 2, &mut audio);
+    assert_eq!(audio.playing, [true]);
+
+    run_frames(&mut chip8, 4, &mut audio);
+    assert_eq!(audio.playing, [true, false]);
+}
+
+#[test]
+fn test_audio_one_frame_beep() {
+    let mut chip8 = chip8_with_source(
+        Chip8::default(),
+        ": main v0 := 1 buzzer := v0 : loop jump loop",
+    );
+    let mut audio = Recorder::default();
+
+    run_frames(&mut chip8, 4, &mut audio);
+
+    assert_eq!(audio.playing, [true, false]);
+}
+
+#[test]
+fn test_audio_pattern_changes() {
+    let mut chip8 = chip8_with_source(
+        Chip8::xo_chip(),
+        ": main i := data audio v0 := 100 pitch := v0 : loop jump loop
+         : data 0xFF 0x00 0xFF 0x00 0xFF 0x00 0xFF 0x00
+                0xFF 0x00 0xFF 0x00 0xFF 0x00 0xFF 0x00",
+    );
+    let mut audio = Recorder::default();
+
+    run_frames(&mut chip8, 6, &mut audio);
+
+    // The first frame, then the frames of F002 and Fx3A
+    assert_eq!(audio.patterns.len(), 3);
+    let (pattern, pitch) = audio.patterns[2];
+    assert_eq!(pattern, [0xFF, 0x00].repeat(8).as_slice());
+    assert_hex_equal!("pitch", 100, pitch);
+}
+
+#[test]
+fn test_backends_as_trait_objects() {
+    let mut chip8 = chip8_with_source(Chip8::default(), ": loop jump loop");
+    let display: &mut dyn Display = &mut Recorder::default();
+    let audio: &mut dyn Audio = &mut Recorder::default();
+
+    chip8
+        .run_frame_with(1, display, &mut (), audio)
+        .expect("Running should succeed");
+}
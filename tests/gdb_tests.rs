@@ -0,0 +1,222 @@
+use hachi::{Chip8, GdbStub, assemble};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread::{self, JoinHandle};
+
+#[macro_use]
+mod common;
+
+use common::chip8_with_program;
+
+const PROGRAM: &str = "
+    : main
+        v0 := 0x12
+        v1 := 0x34
+        i := 0x300
+        save v1
+        exit
+";
+
+/// A minimal scripted GDB client.
+struct Client {
+    stream: TcpStream,
+    reader: BufReader<TcpStream>,
+    acks: bool,
+}
+
+impl Client {
+    fn send_raw(&mut self, packet: &str) {
+        let checksum = packet.bytes().fold(0u8, |sum, byte| sum.wrapping_add(byte));
+        write!(self.stream, "${}#{:02x}", packet, checksum).unwrap();
+    }
+
+    fn read_reply(&mut self) -> String {
+        let mut reply = Vec::new();
+        self.reader.read_until(b'$', &mut reply).unwrap();
+        reply.clear();
+        self.reader.read_until(b'#', &mut reply).unwrap();
+        reply.pop();
+
+        let mut checksum = [0; 2];
+        self.reader.read_exact(&mut checksum).unwrap();
+        let expected = reply.iter().fold(0u8, |sum, &byte| sum.wrapping_add(byte));
+        assert_eq!(
+            std::str::from_utf8(&checksum).unwrap(),
+            format!("{:02x}", expected),
+            "Reply checksum"
+        );
+        if self.acks {
+            self.stream.write_all(b"+").unwrap();
+        }
+
+        String::from_utf8(reply).unwrap()
+    }
+
+    fn send(&mut self, packet: &str) -> String {
+        self.send_raw(packet);
+        if self.acks {
+            let mut ack = [0];
+            self.reader.read_exact(&mut ack).unwrap();
+            assert_eq!(ack[0], b'+', "Packet {} should be acknowledged", packet);
+        }
+        self.read_reply()
+    }
+}
+
+/// Serves `source` on a free local port and connects a client to it.
+fn connect(source: &str) -> (Client, JoinHandle<()>) {
+    let rom = assemble(source).expect("Assembling should succeed");
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+
+    let server = thread::spawn(move || {
+        let chip8 = chip8_with_program(Chip8::default(), &rom);
+        let (stream, _) = listener.accept().unwrap();
+        GdbStub::new(chip8).serve(stream).unwrap();
+    });
+
+    let stream = TcpStream::connect(address).unwrap();
+    stream.set_nodelay(true).unwrap();
+    let reader = BufReader::new(stream.try_clone().unwrap());
+    let client = Client {
+        stream,
+        reader,
+        acks: true,
+    };
+    (client, server)
+}
+
+fn detach(mut client: Client, server: JoinHandle<()>) {
+    assert_eq!(client.send("D"), "OK");
+    server.join().unwrap();
+}
+
+#[test]
+fn test_handshake() {
+    let (mut client, server) = connect(PROGRAM);
+
+    let supported = client.send("qSupported:multiprocess+;swbreak+");
+    assert!(supported.contains("qXfer:features:read+"), "{}", supported);
+    assert_eq!(client.send("?"), "S05");
+    assert_eq!(client.send("vMustReplyEmpty"), "");
+
+    let xml = client.send("qXfer:features:read:target.xml:0,1000");
+    assert!(xml.starts_with("l<?xml"), "{}", xml);
+    assert!(xml.contains("<reg name=\"pc\" bitsize=\"16\""), "{}", xml);
+
+    detach(client, server);
+}
+
+#[test]
+fn test_read_and_write_registers() {
+    let (mut client, server) = connect(PROGRAM);
+
+    let registers = client.send("g");
+    // V0-VF, I, PC, SP, DT, ST
+    assert_eq!(registers, format!("{}00000200000000", "00".repeat(16)));
+
+    assert_eq!(client.send("P3=7f"), "OK");
+    assert_eq!(client.send("p3"), "7f");
+    assert_eq!(client.send("P10=0abc"), "OK");
+    assert_eq!(client.send("p10"), "0abc");
+    assert_eq!(client.send("p11"), "0200");
+    assert_eq!(client.send("p15"), "E01");
+
+    let mut registers = client.send("g");
+    registers.replace_range(2..4, "ff");
+    assert_eq!(client.send(&format!("G{}", registers)), "OK");
+    assert_eq!(client.send("p1"), "ff");
+
+    detach(client, server);
+}
+
+#[test]
+fn test_read_and_write_memory() {
+    let (mut client, server) = connect(PROGRAM);
+
+    assert_eq!(client.send("m200,4"), "60126134");
+    assert_eq!(client.send("m50,5"), "f0909090f0");
+    assert_eq!(client.send("M300,2:beef"), "OK");
+    assert_eq!(client.send("m300,2"), "beef");
+    assert_eq!(client.send("m1000,1"), "E01");
+    assert_eq!(client.send("M300,2:be"), "E01");
+    assert_eq!(client.send("Mffffffffffffffff,1:00"), "E01");
+    // Replies fit in the packet size
+    assert_eq!(client.send("m0,ffffffff").len(), 0x1000);
+
+    detach(client, server);
+}
+
+#[test]
+fn test_single_step() {
+    let (mut client, server) = connect(PROGRAM);
+
+    assert_eq!(client.send("s"), "S05");
+    assert_eq!(client.send("p0"), "12");
+    assert_eq!(client.send("p11"), "0202");
+
+    detach(client, server);
+}
+
+#[test]
+fn test_breakpoint_and_continue() {
+    let (mut client, server) = connect(PROGRAM);
+
+    assert_eq!(client.send("Z0,206,2"), "OK");
+    assert_eq!(client.send("c"), "S05");
+    assert_eq!(client.send("p11"), "0206");
+    assert_eq!(client.send("p10"), "0300");
+
+    assert_eq!(client.send("z0,206,2"), "OK");
+    assert_eq!(client.send("c"), "W00");
+    assert_eq!(client.send("m300,2"), "1234");
+
+    detach(client, server);
+}
+
+#[test]
+fn test_fault_reports_signal() {
+    let (mut client, server) = connect("return");
+
+    assert_eq!(client.send("c"), "S0b");
+    assert_eq!(client.send("p11"), "0200");
+
+    detach(client, server);
+}
+
+#[test]
+fn test_interrupt_stops_continue() {
+    let (mut client, server) = connect(": forever jump forever");
+
+    client.send_raw("c");
+    let mut ack = [0];
+    client.reader.read_exact(&mut ack).unwrap();
+    client.stream.write_all(&[0x03]).unwrap();
+
+    assert_eq!(client.read_reply(), "S02");
+
+    detach(client, server);
+}
+
+#[test]
+fn test_no_ack_mode() {
+    let (mut client, server) = connect(PROGRAM);
+
+    assert_eq!(client.send("QStartNoAckMode"), "OK");
+    client.acks = false;
+    assert_eq!(client.send("p11"), "0200");
+
+    detach(client, server);
+}
+
+#[test]
+fn test_kill_closes_connection() {
+    let (mut client, server) = connect(PROGRAM);
+
+    client.send_raw("k");
+    server.join().unwrap();
+
+    let mut rest = Vec::new();
+    client.reader.read_to_end(&mut rest).unwrap();
+    assert_eq!(rest, b"+");
+}
@@ -0,0 +1,458 @@
+//! End-to-end tests: each case runs a ROM for a number of frames under given
+//! quirks and keypad input, then compares the display with a golden image in
+//! `tests/golden`.
+//!
+//! Goldens are ASCII text (`video_ascii`, which shows XO-CHIP bitplanes), PBM
+//! images or `video_hash` hashes. Run `cargo test --test golden_tests --
+//! --bless` to write them from the current output instead, then review the
+//! changes.
+//!
+//! Cases running Timendus's CHIP-8 test suite need its ROMs in `tests/roms`
+//! (see the README there) and fail when they're missing.
+
+use hachi::{Chip8, ImageFormat, Quirks, Screenshot, assemble};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::{env, fs, process};
+
+/// Where a case's ROM comes from.
+enum Rom {
+    /// Octo source, assembled with `hachi::assemble`.
+    Source(&'static str),
+    /// A ROM file in `tests/roms`.
+    File(&'static str),
+}
+
+/// Format of a golden image.
+#[derive(Clone, Copy)]
+enum Golden {
+    Text,
+    Pbm,
+    /// The `video_hash` in hex. The display is shown on mismatch, and
+    /// written as text to `target/tmp` when blessing, for review.
+    Hash,
+}
+
+enum Outcome {
+    Passed,
+    Blessed,
+}
+
+/// A keypad key held down for a range of frames.
+struct Hold {
+    key: u8,
+    frames: Range<u64>,
+}
+
+struct Case {
+    name: &'static str,
+    rom: Rom,
+    /// A fresh machine with the quirks to test.
+    machine: fn() -> Chip8,
+    instructions_per_frame: usize,
+    frames: u64,
+    keys: &'static [Hold],
+    /// Written to 0x1FF before running. The test suite's quirks and keypad
+    /// ROMs then skip their menu and run that choice.
+    menu_choice: Option<u8>,
+    golden: Golden,
+}
+
+fn cases() -> Vec<Case> {
+    vec![
+        Case {
+            name: "digits",
+            rom: Rom::Source(
+                ": main
+                    v1 := 1 v2 := 1
+                    loop
+                        i := hex v0
+                        sprite v1 v2 5
+                        v1 += 5
+                        if v1 == 41 begin v1 := 1 v2 += 6 end
+                        v0 += 1
+                        if v0 != 16 then
+                    again
+                 : halt jump halt",
+            ),
+            machine: Chip8::default,
+            instructions_per_frame: 20,
+            frames: 20,
+            keys: &[],
+            menu_choice: None,
+            golden: Golden::Text,
+        },
+        Case {
+            name: "keypad",
+            // Draws each key pressed
+            rom: Rom::Source(
+                ": main
+                    v1 := 1 v2 := 1
+                    loop
+                        v0 := key
+                        i := hex v0
+                        sprite v1 v2 5
+                        v1 += 5
+                    again",
+            ),
+            machine: || Chip8::new(Quirks::cosmac_vip()),
+            instructions_per_frame: 11,
+            frames: 40,
+            keys: &[
+                Hold {
+                    key: 0xA,
+                    frames: 5..10,
+                },
+                Hold {
+                    key: 0x3,
+                    frames: 15..20,
+                },
+                Hold {
+                    key: 0xF,
+                    frames: 25..26,
+                },
+            ],
+            menu_choice: None,
+            golden: Golden::Text,
+        },
+        Case {
+            name: "clipping",
+            rom: Rom::Source(
+                ": main v0 := 0x3E v1 := 29 i := hex v0 sprite v0 v1 5 : halt jump halt",
+            ),
+            machine: || Chip8::new(Quirks::cosmac_vip()),
+            instructions_per_frame: 11,
+            frames: 5,
+            keys: &[],
+            menu_choice: None,
+            golden: Golden::Text,
+        },
+        Case {
+            name: "wrapping",
+            rom: Rom::Source(
+                ": main v0 := 0x3E v1 := 29 i := hex v0 sprite v0 v1 5 : halt jump halt",
+            ),
+            machine: || Chip8::new(Quirks::xo_chip()),
+            instructions_per_frame: 11,
+            frames: 5,
+            keys: &[],
+            menu_choice: None,
+            golden: Golden::Text,
+        },
+        Case {
+            name: "schip_hires",
+            rom: Rom::Source(
+                ": main
+                    hires
+                    v1 := 2
+                    loop
+                        i := bighex v0
+                        sprite v1 v1 10
+                        v1 += 10
+                        v0 += 1
+                        if v0 != 10 then
+                    again
+                    scroll-right
+                    scroll-down 4
+                 : halt jump halt",
+            ),
+            machine: || Chip8::new(Quirks::super_chip()),
+            instructions_per_frame: 30,
+            frames: 10,
+            keys: &[],
+            menu_choice: None,
+            golden: Golden::Pbm,
+        },
+        Case {
+            name: "xo_chip_planes",
+            rom: Rom::Source(
+                ": main
+                    plane 1 v0 := 1 i := hex v0 sprite v2 v2 5
+                    plane 2 v2 := 2 sprite v2 v2 5
+                    plane 3 v0 := 8 i := hex v0 v2 := 10 sprite v2 v1 5
+                 : halt jump halt",
+            ),
+            machine: Chip8::xo_chip,
+            instructions_per_frame: 11,
+            frames: 5,
+            keys: &[],
+            menu_choice: None,
+            golden: Golden::Text,
+        },
+        Case {
+            name: "shift_flags",
+            // Shifts into VF, where the flag must win over the result, and
+            // draws VF after each: 1 0 1 0
+            rom: Rom::Source(
+                ": main
+                    v1 := 1 v2 := 1
+                    vF := 0x81 vF <<= vF v0 := vF i := hex v0 sprite v1 v2 5 v1 += 5
+                    vF := 0x40 vF <<= vF v0 := vF i := hex v0 sprite v1 v2 5 v1 += 5
+                    vF := 0x03 vF >>= vF v0 := vF i := hex v0 sprite v1 v2 5 v1 += 5
+                    vF := 0x02 vF >>= vF v0 := vF i := hex v0 sprite v1 v2 5
+                 : halt jump halt",
+            ),
+            machine: || Chip8::new(Quirks::chip48()),
+            instructions_per_frame: 30,
+            frames: 5,
+            keys: &[],
+            menu_choice: None,
+            golden: Golden::Text,
+        },
+        // Timendus's CHIP-8 test suite
+        Case {
+            name: "timendus_ibm_logo",
+            rom: Rom::File("2-ibm-logo.ch8"),
+            machine: Chip8::default,
+            instructions_per_frame: 11,
+            frames: 60,
+            keys: &[],
+            menu_choice: None,
+            golden: Golden::Hash,
+        },
+        Case {
+            name: "timendus_corax_plus",
+            rom: Rom::File("3-corax+.ch8"),
+            machine: Chip8::default,
+            instructions_per_frame: 20,
+            frames: 120,
+            keys: &[],
+            menu_choice: None,
+            golden: Golden::Hash,
+        },
+        Case {
+            name: "timendus_flags",
+            rom: Rom::File("4-flags.ch8"),
+            machine: Chip8::default,
+            instructions_per_frame: 20,
+            frames: 120,
+            keys: &[],
+            menu_choice: None,
+            golden: Golden::Hash,
+        },
+        // The quirks test's platforms: 1 for CHIP-8, 2 for modern SUPER-CHIP
+        // and 3 for XO-CHIP
+        Case {
+            name: "timendus_quirks_chip8",
+            rom: Rom::File("5-quirks.ch8"),
+            machine: || Chip8::new(Quirks::cosmac_vip()),
+            instructions_per_frame: 15,
+            frames: 600,
+            keys: &[],
+            menu_choice: Some(1),
+            golden: Golden::Hash,
+        },
+        Case {
+            name: "timendus_quirks_schip",
+            rom: Rom::File("5-quirks.ch8"),
+            machine: || Chip8::new(Quirks::super_chip()),
+            instructions_per_frame: 30,
+            frames: 600,
+            keys: &[],
+            menu_choice: Some(2),
+            golden: Golden::Hash,
+        },
+        Case {
+            name: "timendus_quirks_xo_chip",
+            rom: Rom::File("5-quirks.ch8"),
+            machine: Chip8::xo_chip,
+            instructions_per_frame: 30,
+            frames: 600,
+            keys: &[],
+            menu_choice: Some(3),
+            golden: Golden::Hash,
+        },
+        // Runs the FX0A test, pressing and releasing A
+        Case {
+            name: "timendus_keypad",
+            rom: Rom::File("6-keypad.ch8"),
+            machine: || Chip8::new(Quirks::cosmac_vip()),
+            instructions_per_frame: 15,
+            frames: 120,
+            keys: &[Hold {
+                key: 0xA,
+                frames: 60..70,
+            }],
+            menu_choice: Some(3),
+            golden: Golden::Hash,
+        },
+    ]
+}
+
+fn tests_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests")
+}
+
+impl Case {
+    fn golden_path(&self) -> PathBuf {
+        let extension = match self.golden {
+            Golden::Text => "txt",
+            Golden::Pbm => "pbm",
+            Golden::Hash => "hash",
+        };
+        tests_dir()
+            .join("golden")
+            .join(format!("{}.{}", self.name, extension))
+    }
+
+    fn rom(&self) -> Result<Vec<u8>, String> {
+        match self.rom {
+            Rom::Source(source) => assemble(source).map_err(|e| e.to_string()),
+            Rom::File(name) => {
+                let path = tests_dir().join("roms").join(name);
+                fs::read(&path).map_err(|e| {
+                    format!(
+                        "Failed to read {}: {} (see tests/roms/README.md)",
+                        path.display(),
+                        e
+                    )
+                })
+            }
+        }
+    }
+
+    /// Runs `rom` for the case's frames with its keypad input.
+    fn run(&self, rom: &[u8]) -> Result<Chip8, String> {
+        let mut chip8 = (self.machine)();
+        chip8.seed_rng(0);
+        chip8.load_rom_from_reader(rom).map_err(|e| e.to_string())?;
+        if let Some(choice) = self.menu_choice {
+            chip8.memory[0x1FF] = choice;
+        }
+
+        for frame in 0..self.frames {
+            chip8.set_keypad(std::array::from_fn(|key| {
+                self.keys
+                    .iter()
+                    .any(|hold| hold.key as usize == key && hold.frames.contains(&frame))
+            }));
+            chip8
+                .run_frame(self.instructions_per_frame)
+                .map_err(|e| format!("Frame {}: {}", frame, e))?;
+        }
+
+        Ok(chip8)
+    }
+
+    /// The display in the golden's format.
+    fn render(&self, chip8: &Chip8) -> Result<Vec<u8>, String> {
+        match self.golden {
+            Golden::Text => Ok(chip8.video_ascii().into_bytes()),
+            Golden::Pbm => {
+                let mut image = Vec::new();
+                Screenshot::new(ImageFormat::Pbm)
+                    .write(chip8, &mut image)
+                    .map_err(|e| e.to_string())?;
+                Ok(image)
+            }
+            Golden::Hash => Ok(format!("{:016x}\n", chip8.video_hash()).into_bytes()),
+        }
+    }
+
+    /// Compares the display with the golden, or writes it when blessing.
+    fn check(&self, bless: bool) -> Result<Outcome, String> {
+        let rom = self.rom()?;
+        let chip8 = self.run(&rom)?;
+        let actual = self.render(&chip8)?;
+        let path = self.golden_path();
+
+        if bless {
+            fs::write(&path, &actual)
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            // A hash can't be reviewed, so the display it was taken from is
+            // kept for review
+            if let Golden::Hash = self.golden {
+                let display_path =
+                    Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!("{}.txt", self.name));
+                fs::write(&display_path, chip8.video_ascii())
+                    .map_err(|e| format!("Failed to write {}: {}", display_path.display(), e))?;
+            }
+            return Ok(Outcome::Blessed);
+        }
+
+        let expected = fs::read(&path).map_err(|e| {
+            format!(
+                "Failed to read {}: {} (run with --bless to create it)",
+                path.display(),
+                e
+            )
+        })?;
+        if actual == expected {
+            return Ok(Outcome::Passed);
+        }
+
+        let actual_path = Path::new(env!("CARGO_TARGET_TMPDIR"))
+            .join(path.file_name().expect("Goldens are files"));
+        fs::write(&actual_path, &actual)
+            .map_err(|e| format!("Failed to write {}: {}", actual_path.display(), e))?;
+        let mut message = format!(
+            "Display differs from {}, actual output in {}",
+            path.display(),
+            actual_path.display()
+        );
+        match self.golden {
+            Golden::Text => {
+                message += &format!(
+                    "\nExpected:\n{}Actual:\n{}",
+                    String::from_utf8_lossy(&expected),
+                    String::from_utf8_lossy(&actual)
+                );
+            }
+            Golden::Pbm => {}
+            Golden::Hash => message += &format!("\nActual:\n{}", chip8.video_ascii()),
+        }
+        Err(message)
+    }
+}
+
+/// A custom harness, so that `--bless` can be passed like a libtest flag.
+/// Other arguments that aren't flags filter the cases by name.
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let bless = args.iter().any(|arg| arg == "--bless");
+    let filters: Vec<&str> = args
+        .iter()
+        .filter(|arg| !arg.starts_with('-'))
+        .map(String::as_str)
+        .collect();
+    let cases: Vec<Case> = cases()
+        .into_iter()
+        .filter(|case| {
+            filters.is_empty() || filters.iter().any(|filter| case.name.contains(filter))
+        })
+        .collect();
+
+    if args.iter().any(|arg| arg == "--list") {
+        for case in &cases {
+            println!("{}: test", case.name);
+        }
+        return;
+    }
+
+    println!("\nrunning {} tests", cases.len());
+    let mut failures = Vec::new();
+    for case in &cases {
+        match case.check(bless) {
+            Ok(Outcome::Passed) => println!("golden {} ... ok", case.name),
+            Ok(Outcome::Blessed) => println!("golden {} ... blessed", case.name),
+            Err(message) => {
+                println!("golden {} ... FAILED", case.name);
+                failures.push((case.name, message));
+            }
+        }
+    }
+
+    for (name, message) in &failures {
+        println!("\n---- {} ----\n{}", name, message);
+    }
+    let result = if failures.is_empty() { "ok" } else { "FAILED" };
+    println!(
+        "\ntest result: {}. {} passed; {} failed\n",
+        result,
+        cases.len() - failures.len(),
+        failures.len()
+    );
+    if !failures.is_empty() {
+        process::exit(1);
+    }
+}
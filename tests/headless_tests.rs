@@ -0,0 +1,121 @@
+use hachi::Chip8;
+use std::process::Command;
+
+#[macro_use]
+mod common;
+
+use common::chip8_with_program;
+
+// Draws the font glyph "0" at (0, 0) and loops forever
+const PROGRAM: [u8; 6] = [
+    0xA0, 0x50, // 0x200: LD I, 0x050
+    0xD0, 0x05, // 0x202: DRW V0, V0, 5
+    0x12, 0x04, // 0x204: JP 0x204
+];
+
+fn chip8_after_drawing() -> Chip8 {
+    let mut chip8 = chip8_with_program(Chip8::default(), &PROGRAM);
+    chip8.run_frame(3).unwrap();
+    chip8
+}
+
+#[test]
+fn test_run_frame_executes_instructions_and_ticks_timers() {
+    let mut chip8 = chip8_with_program(
+        Chip8::default(),
+        &[
+            0x70, 0x01, // 0x200: ADD V0, 0x01
+            0x12, 0x00, // 0x202: JP 0x200
+        ],
+    );
+    chip8.delay_timer = 10;
+
+    chip8.run_frame(10).unwrap();
+
+    assert_hex_equal!("register 0", 5, chip8.registers[0x0]);
+    assert_hex_equal!("delay timer", 9, chip8.delay_timer);
+}
+
+#[test]
+fn test_run_frame_stops_on_error() {
+    let mut chip8 = chip8_with_program(Chip8::default(), &[0x00, 0xEE]); // RET with empty stack
+    chip8.delay_timer = 10;
+
+    let result = chip8.run_frame(10);
+
+    assert!(result.is_err(), "Frame should fail");
+    assert_hex_equal!("delay timer", 10, chip8.delay_timer);
+}
+
+#[test]
+fn test_video_ascii() {
+    let ascii = chip8_after_drawing().video_ascii();
+    let lines: Vec<&str> = ascii.lines().collect();
+
+    assert_eq!(lines.len(), 32);
+    assert!(lines.iter().all(|line| line.len() == 64));
+    assert_eq!(&lines[0][..8], "####....");
+    assert_eq!(&lines[1][..8], "#..#....");
+    assert_eq!(&lines[4][..8], "####....");
+    assert!(lines[5].chars().all(|c| c == '.'), "Row 5 should be blank");
+}
+
+#[test]
+fn test_video_ascii_shows_bitplanes() {
+    let mut chip8 = Chip8::default();
+    chip8.video[..4].copy_from_slice(&[0b00, 0b01, 0b10, 0b11]);
+
+    assert!(chip8.video_ascii().starts_with(".#+@."));
+}
+
+#[test]
+fn test_video_ascii_uses_current_resolution() {
+    let mut chip8 = Chip8::default();
+    chip8.set_hires(true);
+
+    let ascii = chip8.video_ascii();
+
+    assert_eq!(ascii.lines().count(), 64);
+    assert!(ascii.lines().all(|line| line.len() == 128));
+}
+
+#[test]
+fn test_video_hash() {
+    let blank_hash = chip8_with_program(Chip8::default(), &PROGRAM).video_hash();
+    let drawn_hash = chip8_after_drawing().video_hash();
+
+    assert_ne!(blank_hash, drawn_hash, "Drawing should change the hash");
+    assert_eq!(
+        drawn_hash,
+        chip8_after_drawing().video_hash(),
+        "Same framebuffer should hash the same"
+    );
+    assert_eq!(drawn_hash, 0xe6258b11c9b1c95b);
+}
+
+#[test]
+fn test_headless_cli_prints_final_framebuffer() {
+    let rom_path = std::env::temp_dir().join("hachi_headless_cli_test.ch8");
+    std::fs::write(&rom_path, PROGRAM).unwrap();
+
+    let ascii = Command::new(env!("CARGO_BIN_EXE_hachi"))
+        .args(["run", "--headless", "--frames", "10"])
+        .arg(&rom_path)
+        .output()
+        .unwrap();
+    let hash = Command::new(env!("CARGO_BIN_EXE_hachi"))
+        .args(["run", "--headless", "--frames", "10", "--output", "hash"])
+        .arg(&rom_path)
+        .output()
+        .unwrap();
+
+    assert!(ascii.status.success(), "Headless run should succeed");
+    let stdout = String::from_utf8(ascii.stdout).unwrap();
+    assert_eq!(stdout, chip8_after_drawing().video_ascii());
+
+    assert!(hash.status.success(), "Headless run should succeed");
+    assert_eq!(
+        String::from_utf8(hash.stdout).unwrap(),
+        "e6258b11c9b1c95b\n"
+    );
+}
@@ -1,39 +1,22 @@
-use hachi::Chip8;
+use hachi::{Chip8, KeyWait, Sequence};
 
-// Macro for hex values with configurable formatting
-macro_rules! assert_hex_equal {
-    // 8-bit hex format (default)
-    ($name:expr, $expected:expr, $actual:expr) => {
-        assert_eq!(
-            $actual, $expected,
-            "Expected {} to be 0x{:02X}, got 0x{:02X}",
-            $name, $expected, $actual
-        );
-    };
-    // 16-bit hex format
-    ($name:expr, $expected:expr, $actual:expr, 16) => {
-        assert_eq!(
-            $actual, $expected,
-            "Expected {} to be 0x{:04X}, got 0x{:04X}",
-            $name, $expected, $actual
-        );
-    };
-}
+#[macro_use]
+mod common;
 
 #[test]
 fn test_clear_display() {
     let mut chip8 = Chip8 {
-        video: [true; 64 * 32],
+        video: [1; 128 * 64],
         ..Default::default()
     };
 
     let initial_pc = chip8.pc;
     let initial_sp = chip8.sp;
-    let initial_memory = chip8.memory;
+    let initial_memory = chip8.memory.clone();
 
     chip8.clear_display();
 
-    let expected_display = [false; 64 * 32];
+    let expected_display = [0; 128 * 64];
     assert_eq!(
         chip8.video, expected_display,
         "Display should be completely cleared"
@@ -58,7 +41,7 @@ fn test_ret() {
         ..Default::default()
     };
 
-    chip8.ret();
+    chip8.ret().unwrap();
 
     assert_hex_equal!("program counter", 0x200, chip8.pc, 16);
     assert_hex_equal!("stack pointer", 0, chip8.sp);
@@ -66,12 +49,9 @@ fn test_ret() {
 
 #[test]
 fn test_jump() {
-    let mut chip8 = Chip8 {
-        opcode: 0x1A59,
-        ..Default::default()
-    };
+    let mut chip8 = Chip8::default();
 
-    chip8.jump();
+    chip8.jump(0xA59);
 
     assert_hex_equal!("program counter", 0x0A59, chip8.pc, 16);
 }
@@ -80,11 +60,10 @@ fn test_jump() {
 fn test_call() {
     let mut chip8 = Chip8 {
         pc: 0x500,
-        opcode: 0x2A59,
         ..Default::default()
     };
 
-    chip8.call();
+    chip8.call(0xA59).unwrap();
 
     assert_hex_equal!("program counter", 0x0A59, chip8.pc, 16);
     assert_hex_equal!("stack pointer", 1, chip8.sp);
@@ -100,11 +79,10 @@ fn test_skip_equal_vx_byte_should_skip() {
             registers[0] = 0x55;
             registers
         },
-        opcode: 0x3055,
         ..Default::default()
     };
 
-    chip8.skip_equal_vx_byte();
+    chip8.skip_equal_vx_byte(0x0, 0x55);
 
     assert_hex_equal!("program counter", 0x3002, chip8.pc, 16);
 }
@@ -118,11 +96,10 @@ fn test_skip_equal_vx_byte_should_not_skip() {
             registers[0] = 0x55;
             registers
         },
-        opcode: 0x3056,
         ..Default::default()
     };
 
-    chip8.skip_equal_vx_byte();
+    chip8.skip_equal_vx_byte(0x0, 0x56);
 
     assert_hex_equal!("program counter", 0x3000, chip8.pc, 16);
 }
@@ -136,11 +113,10 @@ fn test_skip_not_equal_vx_byte_should_skip() {
             registers[0] = 0x55;
             registers
         },
-        opcode: 0x4056,
         ..Default::default()
     };
 
-    chip8.skip_not_equal_vx_byte();
+    chip8.skip_not_equal_vx_byte(0x0, 0x56);
 
     assert_hex_equal!("program counter", 0x4002, chip8.pc, 16);
 }
@@ -154,11 +130,10 @@ fn test_skip_not_equal_vx_byte_should_not_skip() {
             registers[0] = 0x55;
             registers
         },
-        opcode: 0x4055,
         ..Default::default()
     };
 
-    chip8.skip_not_equal_vx_byte();
+    chip8.skip_not_equal_vx_byte(0x0, 0x55);
 
     assert_hex_equal!("program counter", 0x4000, chip8.pc, 16);
 }
@@ -173,11 +148,10 @@ fn test_skip_equal_vx_vy_should_skip() {
             registers[1] = 0x55;
             registers
         },
-        opcode: 0x5010,
         ..Default::default()
     };
 
-    chip8.skip_equal_vx_vy();
+    chip8.skip_equal_vx_vy(0x0, 0x1);
 
     assert_hex_equal!("program counter", 0x5002, chip8.pc, 16);
 }
@@ -192,23 +166,19 @@ fn test_skip_equal_vx_vy_should_not_skip() {
             registers[1] = 0x56;
             registers
         },
-        opcode: 0x5010,
         ..Default::default()
     };
 
-    chip8.skip_equal_vx_vy();
+    chip8.skip_equal_vx_vy(0x0, 0x1);
 
     assert_hex_equal!("program counter", 0x5000, chip8.pc, 16);
 }
 
 #[test]
 fn test_load_vx_byte() {
-    let mut chip8 = Chip8 {
-        opcode: 0x6A55,
-        ..Default::default()
-    };
+    let mut chip8 = Chip8::default();
 
-    chip8.load_vx_byte();
+    chip8.load_vx_byte(0xA, 0x55);
 
     assert_hex_equal!("register A", 0x55, chip8.registers[0xA]);
 }
@@ -216,7 +186,6 @@ fn test_load_vx_byte() {
 #[test]
 fn test_load_vx_vy() {
     let mut chip8 = Chip8 {
-        opcode: 0x8AB0,
         registers: {
             let mut registers = [0; 16];
             registers[0xA] = 0x05;
@@ -226,7 +195,7 @@ fn test_load_vx_vy() {
         ..Default::default()
     };
 
-    chip8.load_vx_vy();
+    chip8.load_vx_vy(0xA, 0xB);
 
     assert_hex_equal!("register A", 0xF2, chip8.registers[0xA]);
     assert_hex_equal!("register B", 0xF2, chip8.registers[0xB]);
@@ -240,11 +209,10 @@ fn test_add_vx_byte() {
             registers[0xA] = 0x55;
             registers
         },
-        opcode: 0x7A12,
         ..Default::default()
     };
 
-    chip8.add_vx_byte();
+    chip8.add_vx_byte(0xA, 0x12);
 
     assert_hex_equal!("register A", 0x67, chip8.registers[0xA]);
 }
@@ -257,11 +225,10 @@ fn test_add_vx_byte_overflow() {
             registers[0xA] = 0xFF;
             registers
         },
-        opcode: 0x7A02,
         ..Default::default()
     };
 
-    chip8.add_vx_byte();
+    chip8.add_vx_byte(0xA, 0x02);
 
     assert_hex_equal!("register A", 0x01, chip8.registers[0xA]);
 }
@@ -269,7 +236,6 @@ fn test_add_vx_byte_overflow() {
 #[test]
 fn test_or_vx_vy() {
     let mut chip8 = Chip8 {
-        opcode: 0x8AB1,
         registers: {
             let mut registers = [0; 16];
             registers[0xA] = 0x01;
@@ -279,7 +245,7 @@ fn test_or_vx_vy() {
         ..Default::default()
     };
 
-    chip8.or_vx_vy();
+    chip8.or_vx_vy(0xA, 0xB);
 
     assert_hex_equal!("register A", 0x11, chip8.registers[0xA]);
 }
@@ -287,7 +253,6 @@ fn test_or_vx_vy() {
 #[test]
 fn test_and_vx_vy() {
     let mut chip8 = Chip8 {
-        opcode: 0x8AB2,
         registers: {
             let mut registers = [0; 16];
             registers[0xA] = 0x11;
@@ -297,7 +262,7 @@ fn test_and_vx_vy() {
         ..Default::default()
     };
 
-    chip8.and_vx_vy();
+    chip8.and_vx_vy(0xA, 0xB);
 
     assert_hex_equal!("register A", 0x10, chip8.registers[0xA]);
 }
@@ -305,7 +270,6 @@ fn test_and_vx_vy() {
 #[test]
 fn test_xor_vx_vy() {
     let mut chip8 = Chip8 {
-        opcode: 0x8AB3,
         registers: {
             let mut registers = [0; 16];
             registers[0xA] = 0x11;
@@ -315,7 +279,7 @@ fn test_xor_vx_vy() {
         ..Default::default()
     };
 
-    chip8.xor_vx_vy();
+    chip8.xor_vx_vy(0xA, 0xB);
 
     assert_hex_equal!("register A", 0x01, chip8.registers[0xA]);
 }
@@ -323,7 +287,6 @@ fn test_xor_vx_vy() {
 #[test]
 fn test_add_vx_vy_without_overflow() {
     let mut chip8 = Chip8 {
-        opcode: 0x8AB4,
         registers: {
             let mut registers = [0; 16];
             registers[0xA] = 0x10;
@@ -333,7 +296,7 @@ fn test_add_vx_vy_without_overflow() {
         ..Default::default()
     };
 
-    chip8.add_vx_vy();
+    chip8.add_vx_vy(0xA, 0xB);
 
     assert_hex_equal!("register A", 0x11, chip8.registers[0xA]);
     assert_hex_equal!("register F", 0, chip8.registers[0xF]);
@@ -342,7 +305,6 @@ fn test_add_vx_vy_without_overflow() {
 #[test]
 fn test_add_vx_vy_with_overflow() {
     let mut chip8 = Chip8 {
-        opcode: 0x8AB4,
         registers: {
             let mut registers = [0; 16];
             registers[0xA] = 0xFF;
@@ -352,7 +314,7 @@ fn test_add_vx_vy_with_overflow() {
         ..Default::default()
     };
 
-    chip8.add_vx_vy();
+    chip8.add_vx_vy(0xA, 0xB);
 
     assert_hex_equal!("register A", 0x10, chip8.registers[0xA]);
     assert_hex_equal!("register F", 1, chip8.registers[0xF]);
@@ -361,7 +323,6 @@ fn test_add_vx_vy_with_overflow() {
 #[test]
 fn test_sub_vx_vy_with_overflow() {
     let mut chip8 = Chip8 {
-        opcode: 0x8AB5,
         registers: {
             let mut registers = [0; 16];
             registers[0xA] = 0x00;
@@ -371,7 +332,7 @@ fn test_sub_vx_vy_with_overflow() {
         ..Default::default()
     };
 
-    chip8.sub_vx_vy();
+    chip8.sub_vx_vy(0xA, 0xB);
 
     assert_hex_equal!("register A", 0xFF, chip8.registers[0xA]);
     assert_hex_equal!("register F", 0, chip8.registers[0xF]);
@@ -380,7 +341,6 @@ fn test_sub_vx_vy_with_overflow() {
 #[test]
 fn test_sub_vx_vy_without_overflow() {
     let mut chip8 = Chip8 {
-        opcode: 0x8AB5,
         registers: {
             let mut registers = [0; 16];
             registers[0xA] = 0x0A;
@@ -390,7 +350,7 @@ fn test_sub_vx_vy_without_overflow() {
         ..Default::default()
     };
 
-    chip8.sub_vx_vy();
+    chip8.sub_vx_vy(0xA, 0xB);
 
     assert_hex_equal!("register A", 0x09, chip8.registers[0xA]);
     assert_hex_equal!("register F", 1, chip8.registers[0xF]);
@@ -399,7 +359,6 @@ fn test_sub_vx_vy_without_overflow() {
 #[test]
 fn test_shr_vx_with_remainder() {
     let mut chip8 = Chip8 {
-        opcode: 0x8A06,
         registers: {
             let mut registers = [0; 16];
             registers[0xA] = 0x05;
@@ -408,7 +367,7 @@ fn test_shr_vx_with_remainder() {
         ..Default::default()
     };
 
-    chip8.shr_vx();
+    chip8.shr_vx(0xA, 0x0);
 
     assert_hex_equal!("register A", 0x02, chip8.registers[0xA]);
     assert_hex_equal!("register F", 1, chip8.registers[0xF]);
@@ -417,7 +376,6 @@ fn test_shr_vx_with_remainder() {
 #[test]
 fn test_shr_vx_without_remainder() {
     let mut chip8 = Chip8 {
-        opcode: 0x8A06,
         registers: {
             let mut registers = [0; 16];
             registers[0xA] = 0x04;
@@ -426,7 +384,7 @@ fn test_shr_vx_without_remainder() {
         ..Default::default()
     };
 
-    chip8.shr_vx();
+    chip8.shr_vx(0xA, 0x0);
 
     assert_hex_equal!("register A", 0x02, chip8.registers[0xA]);
     assert_hex_equal!("register F", 0, chip8.registers[0xF]);
@@ -435,7 +393,6 @@ fn test_shr_vx_without_remainder() {
 #[test]
 fn test_subn_vx_vy_with_overflow() {
     let mut chip8 = Chip8 {
-        opcode: 0x8AB7,
         registers: {
             let mut registers = [0; 16];
             registers[0xA] = 0x0B;
@@ -445,7 +402,7 @@ fn test_subn_vx_vy_with_overflow() {
         ..Default::default()
     };
 
-    chip8.subn_vx_vy();
+    chip8.subn_vx_vy(0xA, 0xB);
 
     assert_hex_equal!("register A", 0xFF, chip8.registers[0xA]);
     assert_hex_equal!("register F", 0, chip8.registers[0xF]);
@@ -454,7 +411,6 @@ fn test_subn_vx_vy_with_overflow() {
 #[test]
 fn test_subn_vx_vy_without_overflow() {
     let mut chip8 = Chip8 {
-        opcode: 0x8AB7,
         registers: {
             let mut registers = [0; 16];
             registers[0xA] = 0x09;
@@ -464,7 +420,7 @@ fn test_subn_vx_vy_without_overflow() {
         ..Default::default()
     };
 
-    chip8.subn_vx_vy();
+    chip8.subn_vx_vy(0xA, 0xB);
 
     assert_hex_equal!("register A", 0x01, chip8.registers[0xA]);
     assert_hex_equal!("register F", 1, chip8.registers[0xF]);
@@ -473,7 +429,6 @@ fn test_subn_vx_vy_without_overflow() {
 #[test]
 fn test_shl_vx_with_overflow() {
     let mut chip8 = Chip8 {
-        opcode: 0x8ABE,
         registers: {
             let mut registers = [0; 16];
             registers[0xA] = 0xFF;
@@ -482,7 +437,7 @@ fn test_shl_vx_with_overflow() {
         ..Default::default()
     };
 
-    chip8.shl_vx();
+    chip8.shl_vx(0xA, 0xB);
 
     assert_hex_equal!("register A", 0xFE, chip8.registers[0xA]);
     assert_hex_equal!("register F", 0x1, chip8.registers[0xF]);
@@ -491,7 +446,6 @@ fn test_shl_vx_with_overflow() {
 #[test]
 fn test_shl_vx_without_overflow() {
     let mut chip8 = Chip8 {
-        opcode: 0x8ABE,
         registers: {
             let mut registers = [0; 16];
             registers[0xA] = 0x0F;
@@ -500,17 +454,36 @@ fn test_shl_vx_without_overflow() {
         ..Default::default()
     };
 
-    chip8.shl_vx();
+    chip8.shl_vx(0xA, 0xB);
 
     assert_hex_equal!("register A", 0x1E, chip8.registers[0xA]);
     assert_hex_equal!("register F", 0, chip8.registers[0xF]);
 }
 
+#[test]
+fn test_shl_vx_into_vf_keeps_flag() {
+    let mut chip8 = Chip8::default();
+    chip8.registers[0xF] = 0x81;
+
+    chip8.shl_vx(0xF, 0xF);
+
+    assert_hex_equal!("register F", 1, chip8.registers[0xF]);
+}
+
+#[test]
+fn test_shr_vx_into_vf_keeps_flag() {
+    let mut chip8 = Chip8::default();
+    chip8.registers[0xF] = 0x02;
+
+    chip8.shr_vx(0xF, 0xF);
+
+    assert_hex_equal!("register F", 0, chip8.registers[0xF]);
+}
+
 #[test]
 fn test_skip_not_equal_vx_vy_should_skip() {
     let mut chip8 = Chip8 {
         pc: 0x04,
-        opcode: 0x9AB0,
         registers: {
             let mut registers = [0; 16];
             registers[0xA] = 0x0F;
@@ -520,7 +493,7 @@ fn test_skip_not_equal_vx_vy_should_skip() {
         ..Default::default()
     };
 
-    chip8.skip_not_equal_vx_vy();
+    chip8.skip_not_equal_vx_vy(0xA, 0xB);
 
     assert_hex_equal!("program counter", 0x06, chip8.pc, 16);
 }
@@ -529,7 +502,6 @@ fn test_skip_not_equal_vx_vy_should_skip() {
 fn test_skip_not_equal_vx_vy_should_not_skip() {
     let mut chip8 = Chip8 {
         pc: 0x04,
-        opcode: 0x9AB0,
         registers: {
             let mut registers = [0; 16];
             registers[0xA] = 0x0F;
@@ -539,7 +511,7 @@ fn test_skip_not_equal_vx_vy_should_not_skip() {
         ..Default::default()
     };
 
-    chip8.skip_not_equal_vx_vy();
+    chip8.skip_not_equal_vx_vy(0xA, 0xB);
 
     assert_hex_equal!("program counter", 0x04, chip8.pc, 16);
 }
@@ -547,12 +519,11 @@ fn test_skip_not_equal_vx_vy_should_not_skip() {
 #[test]
 fn test_ld_index() {
     let mut chip8 = Chip8 {
-        opcode: 0xA4E2,
         registers: [0; 16],
         ..Default::default()
     };
 
-    chip8.ld_index();
+    chip8.ld_index(0x4E2);
 
     assert_hex_equal!("index", 0x4E2, chip8.index);
 }
@@ -560,7 +531,6 @@ fn test_ld_index() {
 #[test]
 fn test_jump_v0() {
     let mut chip8 = Chip8 {
-        opcode: 0xB4E2,
         registers: {
             let mut registers = [0; 16];
             registers[0] = 0x4;
@@ -569,7 +539,7 @@ fn test_jump_v0() {
         ..Default::default()
     };
 
-    chip8.jump_v0();
+    chip8.jump_v0(0x4E2).unwrap();
 
     assert_hex_equal!("program counter", 0x4E6, chip8.pc, 16);
 }
@@ -577,13 +547,280 @@ fn test_jump_v0() {
 #[test]
 fn test_rnd_vx_byte() {
     let mut chip8 = Chip8 {
-        opcode: 0xC5F0, // RND V5, 0xF0
-        rand_fn: Box::new(|| 0xAB),
+        random: Box::new(Sequence::new(vec![0xAB])),
         ..Default::default()
     };
 
-    chip8.rnd_vx_byte();
+    chip8.rnd_vx_byte(0x5, 0xF0); // RND V5, 0xF0
 
     // 0xAB & 0xF0 = 0xA0
     assert_hex_equal!("register 5", 0xA0, chip8.registers[0x5]);
 }
+
+#[test]
+fn test_load_vx_delay_timer() {
+    let mut chip8 = Chip8 {
+        delay_timer: 0x3C,
+        ..Default::default()
+    };
+
+    chip8.load_vx_delay_timer(0x3);
+
+    assert_hex_equal!("register 3", 0x3C, chip8.registers[0x3]);
+}
+
+#[test]
+fn test_load_vx_key_waits_for_key() {
+    let mut chip8 = Chip8 {
+        pc: 0x202,
+        ..Default::default()
+    };
+
+    chip8.load_vx_key(0x4);
+
+    assert_eq!(chip8.key_wait, KeyWait::Waiting { vx: 0x4 });
+    assert!(chip8.is_waiting_for_key(), "Should wait for a key");
+    assert_hex_equal!("program counter", 0x202, chip8.pc, 16);
+}
+
+#[test]
+fn test_load_vx_key_stores_key_on_release() {
+    let mut chip8 = Chip8::default();
+
+    chip8.load_vx_key(0x4);
+    chip8.press_key(0xB);
+
+    // Still waiting until the key goes back up
+    assert!(chip8.is_waiting_for_key(), "Should wait for key release");
+    assert_hex_equal!("register 4", 0x0, chip8.registers[0x4]);
+
+    chip8.release_key(0xB);
+
+    assert!(!chip8.is_waiting_for_key(), "Should stop waiting");
+    assert_hex_equal!("register 4", 0xB, chip8.registers[0x4]);
+}
+
+#[test]
+fn test_load_delay_timer_vx() {
+    let mut chip8 = Chip8 {
+        registers: {
+            let mut registers = [0; 16];
+            registers[0x5] = 0x20;
+            registers
+        },
+        ..Default::default()
+    };
+
+    chip8.load_delay_timer_vx(0x5);
+
+    assert_hex_equal!("delay timer", 0x20, chip8.delay_timer);
+}
+
+#[test]
+fn test_load_sound_timer_vx() {
+    let mut chip8 = Chip8 {
+        registers: {
+            let mut registers = [0; 16];
+            registers[0x6] = 0x10;
+            registers
+        },
+        ..Default::default()
+    };
+
+    chip8.load_sound_timer_vx(0x6);
+
+    assert_hex_equal!("sound timer", 0x10, chip8.sound_timer);
+}
+
+#[test]
+fn test_add_index_vx() {
+    let mut chip8 = Chip8 {
+        index: 0x300,
+        registers: {
+            let mut registers = [0; 16];
+            registers[0x7] = 0x25;
+            registers
+        },
+        ..Default::default()
+    };
+
+    chip8.add_index_vx(0x7);
+
+    assert_hex_equal!("index", 0x325, chip8.index, 16);
+}
+
+#[test]
+fn test_load_font_vx() {
+    let mut chip8 = Chip8 {
+        registers: {
+            let mut registers = [0; 16];
+            registers[0x8] = 0xA;
+            registers
+        },
+        ..Default::default()
+    };
+
+    chip8.load_font_vx(0x8);
+
+    // Font starts at 0x50 and each character is 5 bytes long
+    assert_hex_equal!("index", 0x50 + 0xA * 5, chip8.index, 16);
+    assert_hex_equal!("first byte of A", 0xF0, chip8.memory[chip8.index as usize]);
+}
+
+#[test]
+fn test_load_bcd_vx() {
+    let mut chip8 = Chip8 {
+        index: 0x300,
+        registers: {
+            let mut registers = [0; 16];
+            registers[0x9] = 254;
+            registers
+        },
+        ..Default::default()
+    };
+
+    chip8.load_bcd_vx(0x9).unwrap();
+
+    assert_hex_equal!("hundreds", 2, chip8.memory[0x300]);
+    assert_hex_equal!("tens", 5, chip8.memory[0x301]);
+    assert_hex_equal!("ones", 4, chip8.memory[0x302]);
+}
+
+#[test]
+fn test_store_registers() {
+    let mut chip8 = Chip8 {
+        index: 0x300,
+        registers: {
+            let mut registers = [0; 16];
+            registers[0x0] = 0x11;
+            registers[0x1] = 0x22;
+            registers[0x2] = 0x33;
+            registers[0x3] = 0x44;
+            registers
+        },
+        ..Default::default()
+    };
+
+    chip8.store_registers(0x2).unwrap();
+
+    assert_eq!(chip8.memory[0x300..0x303], [0x11, 0x22, 0x33]);
+    assert_hex_equal!("memory after V2", 0x00, chip8.memory[0x303]);
+    assert_hex_equal!("index", 0x300, chip8.index, 16);
+}
+
+#[test]
+fn test_load_registers() {
+    let mut chip8 = Chip8 {
+        index: 0x300,
+        memory: {
+            let mut memory = vec![0; 4096];
+            memory[0x300..0x304].copy_from_slice(&[0x11, 0x22, 0x33, 0x44]);
+            memory
+        },
+        ..Default::default()
+    };
+
+    chip8.load_registers(0x2).unwrap();
+
+    assert_eq!(chip8.registers[..3], [0x11, 0x22, 0x33]);
+    assert_hex_equal!("register 3", 0x00, chip8.registers[0x3]);
+    assert_hex_equal!("index", 0x300, chip8.index, 16);
+}
+
+#[test]
+fn test_tick_timers() {
+    let mut chip8 = Chip8 {
+        delay_timer: 2,
+        sound_timer: 1,
+        ..Default::default()
+    };
+
+    chip8.tick_timers();
+
+    assert_hex_equal!("delay timer", 1, chip8.delay_timer);
+    assert_hex_equal!("sound timer", 0, chip8.sound_timer);
+
+    chip8.tick_timers();
+
+    // Timers stop at zero
+    assert_hex_equal!("delay timer", 0, chip8.delay_timer);
+    assert_hex_equal!("sound timer", 0, chip8.sound_timer);
+}
+
+#[test]
+fn test_skip_key_pressed_vx_should_skip() {
+    let mut chip8 = Chip8 {
+        pc: 0x202,
+        registers: {
+            let mut registers = [0; 16];
+            registers[0x1] = 0x7;
+            registers
+        },
+        keypad: {
+            let mut keypad = [false; 16];
+            keypad[0x7] = true;
+            keypad
+        },
+        ..Default::default()
+    };
+
+    chip8.skip_key_pressed_vx(0x1);
+
+    assert_hex_equal!("program counter", 0x204, chip8.pc, 16);
+}
+
+#[test]
+fn test_skip_key_pressed_vx_should_not_skip() {
+    let mut chip8 = Chip8 {
+        pc: 0x202,
+        registers: {
+            let mut registers = [0; 16];
+            registers[0x1] = 0x7;
+            registers
+        },
+        ..Default::default()
+    };
+
+    chip8.skip_key_pressed_vx(0x1);
+
+    assert_hex_equal!("program counter", 0x202, chip8.pc, 16);
+}
+
+#[test]
+fn test_skip_key_not_pressed_vx_should_skip() {
+    let mut chip8 = Chip8 {
+        pc: 0x202,
+        registers: {
+            let mut registers = [0; 16];
+            registers[0x1] = 0x7;
+            registers
+        },
+        ..Default::default()
+    };
+
+    chip8.skip_key_not_pressed_vx(0x1);
+
+    assert_hex_equal!("program counter", 0x204, chip8.pc, 16);
+}
+
+#[test]
+fn test_skip_key_not_pressed_vx_should_not_skip() {
+    let mut chip8 = Chip8 {
+        pc: 0x202,
+        registers: {
+            let mut registers = [0; 16];
+            registers[0x1] = 0x7;
+            registers
+        },
+        keypad: {
+            let mut keypad = [false; 16];
+            keypad[0x7] = true;
+            keypad
+        },
+        ..Default::default()
+    };
+
+    chip8.skip_key_not_pressed_vx(0x1);
+
+    assert_hex_equal!("program counter", 0x202, chip8.pc, 16);
+}
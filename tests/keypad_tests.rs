@@ -0,0 +1,101 @@
+use hachi::Chip8;
+
+#[macro_use]
+mod common;
+
+use common::chip8_with_program;
+
+fn chip8_waiting_for_key() -> Chip8 {
+    let program = [
+        0xF3, 0x0A, // 0x200: LD V3, K
+        0x64, 0x01, // 0x202: LD V4, 0x01
+    ];
+    let mut chip8 = chip8_with_program(Chip8::default(), &program);
+
+    chip8.cycle().unwrap();
+    chip8
+}
+
+#[test]
+fn test_press_and_release_key_update_keypad() {
+    let mut chip8 = Chip8::default();
+
+    chip8.press_key(0xC);
+    assert!(chip8.keypad[0xC], "Key C should be held");
+
+    chip8.release_key(0xC);
+    assert!(!chip8.keypad[0xC], "Key C should be released");
+}
+
+#[test]
+fn test_cycle_halts_while_waiting_for_key() {
+    let mut chip8 = chip8_waiting_for_key();
+
+    for _ in 0..10 {
+        chip8.cycle().unwrap();
+    }
+
+    assert!(chip8.is_waiting_for_key(), "Should still wait for a key");
+    assert_hex_equal!("program counter", 0x202, chip8.pc, 16);
+    assert_hex_equal!("register 4", 0x00, chip8.registers[0x4]);
+}
+
+#[test]
+fn test_cycle_halts_until_key_is_released() {
+    let mut chip8 = chip8_waiting_for_key();
+
+    chip8.press_key(0x5);
+    chip8.cycle().unwrap();
+
+    assert!(chip8.is_waiting_for_key(), "Should wait for key release");
+    assert_hex_equal!("program counter", 0x202, chip8.pc, 16);
+
+    chip8.release_key(0x5);
+    chip8.cycle().unwrap();
+
+    assert_hex_equal!("register 3", 0x5, chip8.registers[0x3]);
+    assert_hex_equal!("register 4", 0x01, chip8.registers[0x4]);
+    assert_hex_equal!("program counter", 0x204, chip8.pc, 16);
+}
+
+#[test]
+fn test_releasing_other_key_keeps_waiting() {
+    let mut chip8 = chip8_waiting_for_key();
+
+    chip8.press_key(0x5);
+    chip8.press_key(0x6);
+    chip8.release_key(0x6);
+
+    assert!(chip8.is_waiting_for_key(), "Should wait for key 5 release");
+
+    chip8.release_key(0x5);
+
+    assert!(!chip8.is_waiting_for_key(), "Should stop waiting");
+    assert_hex_equal!("register 3", 0x5, chip8.registers[0x3]);
+}
+
+#[test]
+fn test_key_held_before_wait_is_ignored() {
+    let mut chip8 = Chip8::default();
+    chip8.press_key(0x2);
+
+    let program = [0xF3, 0x0A]; // LD V3, K
+    let mut chip8 = chip8_with_program(chip8, &program);
+    chip8.cycle().unwrap();
+    chip8.release_key(0x2);
+
+    assert!(
+        chip8.is_waiting_for_key(),
+        "Should wait for a new key press"
+    );
+}
+
+#[test]
+fn test_timers_tick_while_waiting_for_key() {
+    let mut chip8 = chip8_waiting_for_key();
+    chip8.delay_timer = 3;
+
+    chip8.tick_timers();
+
+    assert_hex_equal!("delay timer", 2, chip8.delay_timer);
+}
@@ -58,7 +58,7 @@ fn test_load_rom_from_reader_empty_data() {
     let cursor = Cursor::new(Vec::new());
 
     let mut chip8 = Chip8::default();
-    let initial_memory = chip8.memory;
+    let initial_memory = chip8.memory.clone();
 
     // Load empty ROM
     let result = chip8.load_rom_from_reader(cursor);
@@ -0,0 +1,183 @@
+use hachi::{Chip8, Movie, Quirks, assemble};
+use std::process::Command;
+
+#[macro_use]
+mod common;
+
+use common::chip8_with_program;
+
+// Waits for a key, then draws digits at random positions, counting up
+// while key 5 is held
+const PROGRAM: &str = "
+    : main
+        v4 := key
+    : loop
+        v0 := random 63
+        v1 := random 31
+        i := hex v2
+        sprite v0 v1 5
+        v3 := 5
+        if v3 key then v2 += 1
+        jump loop
+";
+
+fn rom() -> Vec<u8> {
+    assemble(PROGRAM).expect("Assembling should succeed")
+}
+
+/// Records 40 frames, pressing key A on frames 2-3 and key 5 on frames
+/// 10-19, and returns the movie with the machine it ran on.
+fn record() -> (Movie, Chip8) {
+    let rom = rom();
+    let mut chip8 = chip8_with_program(Chip8::new(Quirks::cosmac_vip()), &rom);
+    chip8.seed_rng(1234);
+    let mut movie = Movie::new(&rom, &chip8, 1234, 8);
+
+    for frame in 0..40 {
+        match frame {
+            2 => chip8.press_key(0xA),
+            4 => chip8.release_key(0xA),
+            10 => chip8.press_key(0x5),
+            20 => chip8.release_key(0x5),
+            _ => {}
+        }
+        movie.record_frame(&chip8);
+        chip8.run_frame(8).unwrap();
+    }
+
+    (movie, chip8)
+}
+
+fn replay(movie: &Movie) -> Chip8 {
+    let mut chip8 = movie
+        .machine(&rom())
+        .expect("Creating the machine should succeed");
+    let mut frame = 0;
+    while movie.play_frame(frame, &mut chip8) {
+        chip8.run_frame(movie.instructions_per_frame).unwrap();
+        frame += 1;
+    }
+    chip8
+}
+
+#[test]
+fn test_seeded_rng_is_deterministic() {
+    let random_bytes = |seed| {
+        let mut chip8 = Chip8::default();
+        chip8.seed_rng(seed);
+        (0..16)
+            .map(|_| chip8.random.next_byte())
+            .collect::<Vec<u8>>()
+    };
+
+    assert_eq!(random_bytes(42), random_bytes(42));
+    assert_ne!(random_bytes(42), random_bytes(43));
+}
+
+#[test]
+fn test_movie_records_keypad_per_frame() {
+    let (movie, _) = record();
+
+    assert_eq!(movie.len(), 40);
+    assert_eq!(movie.frames[1], 0);
+    assert_eq!(movie.frames[2], 1 << 0xA);
+    assert_eq!(movie.frames[15], 1 << 0x5);
+    assert_eq!(movie.frames[39], 0);
+    assert_eq!(movie.seed, 1234);
+    assert_eq!(movie.quirks, Quirks::cosmac_vip());
+    assert_eq!(movie.memory_size, 4096);
+}
+
+#[test]
+fn test_replay_reproduces_session() {
+    let (movie, recorded) = record();
+
+    let replayed = replay(&movie);
+
+    assert_hex_equal!("key register", 0xA, replayed.registers[0x4]);
+    assert_hex_equal!(
+        "digit register",
+        recorded.registers[0x2],
+        replayed.registers[0x2]
+    );
+    assert_eq!(replayed.registers, recorded.registers);
+    assert_eq!(replayed.memory, recorded.memory);
+    assert_eq!(replayed.video_ascii(), recorded.video_ascii());
+}
+
+#[test]
+fn test_movie_file_round_trip() {
+    let (movie, _) = record();
+    let mut file = Vec::new();
+    movie.write(&mut file).unwrap();
+
+    assert_eq!(&file[..8], b"HACHIMOV");
+    assert_eq!(Movie::read(file.as_slice()).unwrap(), movie);
+}
+
+#[test]
+fn test_movie_file_is_checked() {
+    let (movie, _) = record();
+    let mut file = Vec::new();
+    movie.write(&mut file).unwrap();
+
+    file[30] ^= 1;
+    let error = Movie::read(file.as_slice()).expect_err("Reading should fail");
+    assert!(error.to_string().contains("checksum mismatch"), "{}", error);
+
+    let mut save_state = Vec::new();
+    Chip8::default().save_state(&mut save_state).unwrap();
+    let error = Movie::read(save_state.as_slice()).expect_err("Reading should fail");
+    assert!(error.to_string().contains("not a movie"), "{}", error);
+}
+
+#[test]
+fn test_machine_rejects_other_rom() {
+    let (movie, _) = record();
+
+    let error = movie
+        .machine(&[0x00, 0xE0])
+        .err()
+        .expect("Creating the machine should fail");
+
+    assert!(error.to_string().contains("ROM doesn't match"), "{}", error);
+}
+
+#[test]
+fn test_cli_record_and_replay() {
+    let dir = std::env::temp_dir();
+    let rom_path = dir.join("hachi_movie_cli_test.ch8");
+    let movie_path = dir.join("hachi_movie_cli_test.movie");
+    // Draws random digits, without waiting for a key
+    let rom =
+        assemble(": loop v0 := random 63 v1 := random 31 i := hex v0 sprite v0 v1 5 jump loop")
+            .unwrap();
+    std::fs::write(&rom_path, rom).unwrap();
+
+    let hachi = || {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_hachi"));
+        command.args(["run", "--headless", "--output", "hash"]);
+        command
+    };
+    let recorded = hachi()
+        .args(["--frames", "30", "--record"])
+        .arg(&movie_path)
+        .arg(&rom_path)
+        .output()
+        .unwrap();
+    let replayed = hachi()
+        .arg("--replay")
+        .arg(&movie_path)
+        .arg(&rom_path)
+        .output()
+        .unwrap();
+
+    assert!(recorded.status.success(), "Recording should succeed");
+    assert!(replayed.status.success(), "Replaying should succeed");
+    assert_eq!(
+        String::from_utf8(replayed.stdout).unwrap(),
+        String::from_utf8(recorded.stdout).unwrap()
+    );
+
+    std::fs::remove_file(movie_path).unwrap();
+}
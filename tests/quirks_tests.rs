@@ -0,0 +1,334 @@
+use hachi::{Chip8, Quirks};
+
+#[macro_use]
+mod common;
+
+use common::chip8_with_program;
+
+fn chip8_with_quirks(quirks: Quirks) -> Chip8 {
+    Chip8 {
+        registers: {
+            let mut registers = [0; 16];
+            registers[0x1] = 0x81;
+            registers[0x2] = 0x03;
+            registers[0xF] = 0xAA;
+            registers
+        },
+        ..Chip8::new(quirks)
+    }
+}
+
+#[test]
+fn test_default_quirks_are_disabled() {
+    let chip8 = Chip8::default();
+
+    assert_eq!(chip8.quirks, Quirks::default());
+    assert!(!chip8.quirks.shift_uses_vy);
+    assert!(!chip8.quirks.memory_increments_index);
+    assert!(!chip8.quirks.jump_uses_vx);
+    assert!(!chip8.quirks.clip_sprites);
+    assert!(!chip8.quirks.vf_reset);
+    assert!(!chip8.quirks.display_wait);
+}
+
+#[test]
+fn test_new_uses_given_quirks() {
+    let chip8 = Chip8::new(Quirks::cosmac_vip());
+
+    assert_eq!(chip8.quirks, Quirks::cosmac_vip());
+    assert_hex_equal!("program counter", 0x200, chip8.pc, 16);
+}
+
+#[test]
+fn test_shift_right_uses_vy() {
+    let mut chip8 = chip8_with_quirks(Quirks {
+        shift_uses_vy: true,
+        ..Default::default()
+    });
+
+    chip8.shr_vx(0x1, 0x2);
+
+    assert_hex_equal!("register 1", 0x01, chip8.registers[0x1]);
+    assert_hex_equal!("register F", 1, chip8.registers[0xF]);
+}
+
+#[test]
+fn test_shift_right_ignores_vy() {
+    let mut chip8 = chip8_with_quirks(Quirks::default());
+
+    chip8.shr_vx(0x1, 0x2);
+
+    assert_hex_equal!("register 1", 0x40, chip8.registers[0x1]);
+    assert_hex_equal!("register 2", 0x03, chip8.registers[0x2]);
+    assert_hex_equal!("register F", 1, chip8.registers[0xF]);
+}
+
+#[test]
+fn test_shift_left_uses_vy() {
+    let mut chip8 = chip8_with_quirks(Quirks {
+        shift_uses_vy: true,
+        ..Default::default()
+    });
+
+    chip8.shl_vx(0x1, 0x2);
+
+    assert_hex_equal!("register 1", 0x06, chip8.registers[0x1]);
+    assert_hex_equal!("register F", 0, chip8.registers[0xF]);
+}
+
+#[test]
+fn test_shift_left_ignores_vy() {
+    let mut chip8 = chip8_with_quirks(Quirks::default());
+
+    chip8.shl_vx(0x1, 0x2);
+
+    assert_hex_equal!("register 1", 0x02, chip8.registers[0x1]);
+    assert_hex_equal!("register F", 1, chip8.registers[0xF]);
+}
+
+#[test]
+fn test_memory_increments_index() {
+    let mut chip8 = Chip8 {
+        index: 0x300,
+        ..chip8_with_quirks(Quirks {
+            memory_increments_index: true,
+            ..Default::default()
+        })
+    };
+
+    chip8.store_registers(0x2).unwrap();
+    assert_hex_equal!("index after store", 0x303, chip8.index, 16);
+
+    chip8.load_registers(0x1).unwrap();
+    assert_hex_equal!("index after load", 0x305, chip8.index, 16);
+}
+
+#[test]
+fn test_memory_leaves_index_unchanged() {
+    let mut chip8 = Chip8 {
+        index: 0x300,
+        ..chip8_with_quirks(Quirks::default())
+    };
+
+    chip8.store_registers(0x2).unwrap();
+    chip8.load_registers(0x1).unwrap();
+
+    assert_hex_equal!("index", 0x300, chip8.index, 16);
+}
+
+#[test]
+fn test_jump_uses_vx() {
+    let mut chip8 = chip8_with_quirks(Quirks {
+        jump_uses_vx: true,
+        ..Default::default()
+    });
+
+    // B2F0 jumps to 0x2F0 + V2
+    chip8.jump_v0(0x2F0).unwrap();
+
+    assert_hex_equal!("program counter", 0x2F3, chip8.pc, 16);
+}
+
+#[test]
+fn test_jump_uses_v0() {
+    let mut chip8 = Chip8 {
+        registers: {
+            let mut registers = [0; 16];
+            registers[0x0] = 0x10;
+            registers[0x2] = 0x03;
+            registers
+        },
+        ..Default::default()
+    };
+
+    chip8.jump_v0(0x2F0).unwrap();
+
+    assert_hex_equal!("program counter", 0x300, chip8.pc, 16);
+}
+
+#[test]
+fn test_vf_reset_on_logic_operations() {
+    let quirks = Quirks {
+        vf_reset: true,
+        ..Default::default()
+    };
+
+    let mut chip8 = chip8_with_quirks(quirks);
+    chip8.or_vx_vy(0x1, 0x2);
+    assert_hex_equal!("register F after OR", 0, chip8.registers[0xF]);
+
+    let mut chip8 = chip8_with_quirks(quirks);
+    chip8.and_vx_vy(0x1, 0x2);
+    assert_hex_equal!("register F after AND", 0, chip8.registers[0xF]);
+
+    let mut chip8 = chip8_with_quirks(quirks);
+    chip8.xor_vx_vy(0x1, 0x2);
+    assert_hex_equal!("register F after XOR", 0, chip8.registers[0xF]);
+}
+
+#[test]
+fn test_vf_kept_on_logic_operations() {
+    let mut chip8 = chip8_with_quirks(Quirks::default());
+
+    chip8.or_vx_vy(0x1, 0x2);
+
+    assert_hex_equal!("register F", 0xAA, chip8.registers[0xF]);
+}
+
+fn chip8_drawing_at_edge(quirks: Quirks) -> Chip8 {
+    let mut chip8 = Chip8 {
+        index: 0x300,
+        registers: {
+            let mut registers = [0; 16];
+            registers[0x0] = 60; // x: only 4 columns fit on screen
+            registers[0x1] = 31; // y: only 1 row fits on screen
+            registers
+        },
+        ..Chip8::new(quirks)
+    };
+    chip8.memory[0x300] = 0xFF;
+    chip8.memory[0x301] = 0xFF;
+
+    chip8.draw_vx_vy_n(0x0, 0x1, 2).unwrap();
+    chip8
+}
+
+#[test]
+fn test_sprites_wrap_around() {
+    let chip8 = chip8_drawing_at_edge(Quirks::default());
+
+    assert_ne!(
+        chip8.video[31 * 64 + 63],
+        0,
+        "Pixel at the edge should be on"
+    );
+    assert_ne!(chip8.video[31 * 64], 0, "Row 31 should wrap to column 0");
+    assert_ne!(
+        chip8.video[3], 0,
+        "Row 0 should hold the wrapped second row"
+    );
+    assert_eq!(chip8.video.iter().filter(|&&pixel| pixel != 0).count(), 16);
+}
+
+#[test]
+fn test_sprites_clip_at_edges() {
+    let chip8 = chip8_drawing_at_edge(Quirks {
+        clip_sprites: true,
+        ..Default::default()
+    });
+
+    assert_ne!(
+        chip8.video[31 * 64 + 63],
+        0,
+        "Pixel at the edge should be on"
+    );
+    assert_eq!(
+        chip8.video[31 * 64],
+        0,
+        "Pixels past the right edge are clipped"
+    );
+    assert_eq!(
+        chip8.video[63], 0,
+        "Pixels past the bottom edge are clipped"
+    );
+    assert_eq!(chip8.video.iter().filter(|&&pixel| pixel != 0).count(), 4);
+}
+
+#[test]
+fn test_clipped_sprite_start_position_wraps() {
+    let mut chip8 = Chip8 {
+        index: 0x300,
+        registers: {
+            let mut registers = [0; 16];
+            registers[0x0] = 64 + 2;
+            registers[0x1] = 32 + 1;
+            registers
+        },
+        ..Chip8::new(Quirks {
+            clip_sprites: true,
+            ..Default::default()
+        })
+    };
+    chip8.memory[0x300] = 0x80;
+
+    chip8.draw_vx_vy_n(0x0, 0x1, 1).unwrap();
+
+    assert_ne!(chip8.video[64 + 2], 0, "Pixel should be drawn at (2, 1)");
+}
+
+#[test]
+fn test_dxy0_draws_nothing_on_cosmac_vip() {
+    let mut chip8 = Chip8 {
+        index: 0x300,
+        ..Chip8::new(Quirks::cosmac_vip())
+    };
+    chip8.memory[0x300..0x320].fill(0xFF);
+    chip8.registers[0xF] = 1;
+
+    chip8.draw_vx_vy_n(0x0, 0x0, 0).unwrap();
+
+    assert!(chip8.video.iter().all(|&pixel| pixel == 0));
+    assert_hex_equal!("register F", 0, chip8.registers[0xF]);
+}
+
+#[test]
+fn test_dxy0_draws_16x16_sprite_on_super_chip() {
+    let mut chip8 = Chip8 {
+        index: 0x300,
+        ..Chip8::new(Quirks::super_chip())
+    };
+    chip8.memory[0x300..0x320].fill(0xFF);
+
+    chip8.draw_vx_vy_n(0x0, 0x0, 0).unwrap();
+
+    assert_eq!(chip8.video.iter().filter(|&&pixel| pixel != 0).count(), 256);
+}
+
+#[test]
+fn test_display_wait_allows_one_draw_per_frame() {
+    let program = [
+        0xD0, 0x01, // 0x200: DRW V0, V0, 1
+        0x12, 0x00, // 0x202: JP 0x200
+    ];
+    let mut chip8 = chip8_with_program(
+        Chip8::new(Quirks {
+            display_wait: true,
+            ..Default::default()
+        }),
+        &program,
+    );
+
+    for _ in 0..10 {
+        chip8.cycle().unwrap();
+    }
+
+    assert!(
+        chip8.waiting_for_vblank,
+        "Should wait for the vertical blank"
+    );
+    assert_hex_equal!("program counter", 0x202, chip8.pc, 16);
+
+    chip8.tick_timers();
+    chip8.cycle().unwrap();
+
+    assert!(!chip8.waiting_for_vblank, "Should run after the timer tick");
+    assert_hex_equal!("program counter", 0x200, chip8.pc, 16);
+}
+
+#[test]
+fn test_presets() {
+    let vip = Quirks::cosmac_vip();
+    assert!(vip.shift_uses_vy && vip.memory_increments_index && vip.vf_reset);
+    assert!(vip.clip_sprites && vip.display_wait && !vip.jump_uses_vx);
+
+    let schip = Quirks::super_chip();
+    assert!(!schip.shift_uses_vy && !schip.memory_increments_index && !schip.vf_reset);
+    assert!(schip.clip_sprites && !schip.display_wait && schip.jump_uses_vx);
+
+    let chip48 = Quirks::chip48();
+    assert!(!chip48.shift_uses_vy && chip48.jump_uses_vx);
+
+    let xo = Quirks::xo_chip();
+    assert!(xo.shift_uses_vy && xo.memory_increments_index && !xo.clip_sprites);
+    assert!(!xo.vf_reset && !xo.display_wait && !xo.jump_uses_vx);
+}
@@ -0,0 +1,98 @@
+use hachi::{Chip8, RandomSource, Rewind, Sequence, Xorshift};
+
+#[macro_use]
+mod common;
+
+use common::chip8_with_source;
+
+fn bytes(source: &mut dyn RandomSource, n: usize) -> Vec<u8> {
+    (0..n).map(|_| source.next_byte()).collect()
+}
+
+#[test]
+fn test_xorshift_is_deterministic() {
+    let sequence = bytes(&mut Xorshift::new(7), 32);
+
+    assert_eq!(bytes(&mut Xorshift::new(7), 32), sequence);
+    assert_ne!(bytes(&mut Xorshift::new(8), 32), sequence);
+    assert!(
+        sequence.iter().any(|&byte| byte != sequence[0]),
+        "Bytes should vary"
+    );
+}
+
+#[test]
+fn test_xorshift_zero_seed_and_state() {
+    let mut xorshift = Xorshift::new(0);
+    xorshift.set_state(0);
+
+    assert_ne!(xorshift.state(), 0, "A zero state would only produce zeros");
+    assert!(bytes(&mut xorshift, 8).iter().any(|&byte| byte != 0));
+}
+
+#[test]
+fn test_state_restores_sequence() {
+    let mut xorshift = Xorshift::new(99);
+    bytes(&mut xorshift, 5);
+    let state = xorshift.state();
+    let expected = bytes(&mut xorshift, 10);
+
+    let mut restored = Xorshift::new(1);
+    restored.set_state(state);
+
+    assert_eq!(bytes(&mut restored, 10), expected);
+}
+
+#[test]
+fn test_sequence_repeats() {
+    let mut sequence = Sequence::new(vec![1, 2, 3]);
+
+    assert_eq!(bytes(&mut sequence, 7), [1, 2, 3, 1, 2, 3, 1]);
+
+    sequence.set_state(2);
+    assert_eq!(sequence.next_byte(), 3);
+}
+
+#[test]
+fn test_boxed_source_clones_state() {
+    let mut source: Box<dyn RandomSource> = Box::new(Xorshift::new(3));
+    bytes(source.as_mut(), 4);
+
+    let mut clone = source.clone();
+
+    assert_eq!(bytes(clone.as_mut(), 8), bytes(source.as_mut(), 8));
+}
+
+fn chip8_drawing_randomly() -> Chip8 {
+    chip8_with_source(Chip8::default(), ": loop v0 := random 0xFF jump loop")
+}
+
+#[test]
+fn test_save_state_restores_random_state() {
+    let mut chip8 = chip8_drawing_randomly();
+    chip8.run_frame(4).unwrap();
+    let mut state = Vec::new();
+    chip8.save_state(&mut state).unwrap();
+    chip8.run_frame(2).unwrap();
+    let expected = chip8.registers[0x0];
+
+    let mut restored = chip8_drawing_randomly();
+    restored.load_state(state.as_slice()).unwrap();
+    restored.run_frame(2).unwrap();
+
+    assert_hex_equal!("register 0", expected, restored.registers[0x0]);
+}
+
+#[test]
+fn test_rewind_restores_random_state() {
+    let mut chip8 = chip8_drawing_randomly();
+    let mut history = Rewind::new(10);
+    history.push(&chip8);
+    chip8.run_frame(10).unwrap();
+    let expected = chip8.registers[0x0];
+
+    history.pop(&mut chip8);
+    chip8.run_frame(10).unwrap();
+
+    assert_hex_equal!("register 0", expected, chip8.registers[0x0]);
+}
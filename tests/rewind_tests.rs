@@ -0,0 +1,165 @@
+use hachi::{Chip8, Debugger, Rewind};
+
+#[macro_use]
+mod common;
+
+use common::chip8_with_source;
+
+// Counts frames in v0 and draws them
+const PROGRAM: &str = "
+    : main
+        i := hex v0
+        sprite v1 v1 5
+        v0 += 1
+        v2 := 1
+        delay := v2
+    : wait
+        v2 := delay
+        if v2 != 0 then jump wait
+        i := hex v0
+        jump main
+";
+
+fn save(chip8: &Chip8) -> Vec<u8> {
+    let mut state = Vec::new();
+    chip8.save_state(&mut state).unwrap();
+    state
+}
+
+/// Runs `frames` frames, pushing the state before each one, and returns the
+/// saved states.
+fn run_with_history(chip8: &mut Chip8, history: &mut Rewind, frames: usize) -> Vec<Vec<u8>> {
+    (0..frames)
+        .map(|_| {
+            let state = save(chip8);
+            history.push(chip8);
+            chip8.run_frame(10).unwrap();
+            state
+        })
+        .collect()
+}
+
+#[test]
+fn test_rewind_restores_earlier_frames() {
+    let mut chip8 = chip8_with_source(Chip8::default(), PROGRAM);
+    let mut history = Rewind::new(100);
+    let states = run_with_history(&mut chip8, &mut history, 30);
+
+    assert!(history.pop(&mut chip8));
+    assert_eq!(save(&chip8), states[29]);
+
+    assert_eq!(history.rewind(&mut chip8, 10), 10);
+    assert_eq!(save(&chip8), states[19]);
+    assert_eq!(history.len(), 19);
+}
+
+#[test]
+fn test_rewind_all_the_way_back() {
+    let mut chip8 = chip8_with_source(Chip8::default(), PROGRAM);
+    let mut history = Rewind::new(100);
+    let states = run_with_history(&mut chip8, &mut history, 5);
+
+    assert_eq!(history.rewind(&mut chip8, 50), 5);
+
+    assert_eq!(save(&chip8), states[0]);
+    assert!(history.is_empty());
+    assert!(!history.pop(&mut chip8), "Empty history can't be popped");
+    assert_eq!(save(&chip8), states[0]);
+}
+
+#[test]
+fn test_history_drops_oldest_snapshots() {
+    let mut chip8 = chip8_with_source(Chip8::default(), PROGRAM);
+    let mut history = Rewind::new(3);
+    let states = run_with_history(&mut chip8, &mut history, 10);
+
+    assert_eq!(history.len(), 3);
+    assert_eq!(history.rewind(&mut chip8, 5), 3);
+    assert_eq!(save(&chip8), states[7]);
+}
+
+#[test]
+fn test_zero_capacity_keeps_nothing() {
+    let mut chip8 = chip8_with_source(Chip8::default(), PROGRAM);
+    let mut history = Rewind::new(0);
+    run_with_history(&mut chip8, &mut history, 3);
+
+    assert!(history.is_empty());
+}
+
+#[test]
+fn test_snapshots_are_delta_compressed() {
+    let mut chip8 = chip8_with_source(Chip8::default(), PROGRAM);
+    let mut history = Rewind::new(100);
+    let states = run_with_history(&mut chip8, &mut history, 100);
+
+    let full_size = states[0].len();
+    assert!(
+        history.size() < 2 * full_size,
+        "100 snapshots take {} bytes, one is {}",
+        history.size(),
+        full_size
+    );
+}
+
+#[test]
+fn test_memory_size_change_resets_history() {
+    let mut chip8 = chip8_with_source(Chip8::default(), PROGRAM);
+    let mut history = Rewind::new(100);
+    run_with_history(&mut chip8, &mut history, 3);
+
+    chip8.memory.resize(0x10000, 0);
+    history.push(&chip8);
+
+    assert_eq!(history.len(), 1);
+    assert!(history.pop(&mut chip8));
+    assert_eq!(chip8.memory.len(), 0x10000);
+}
+
+#[test]
+fn test_debugger_reverse_step() {
+    let mut debugger = Debugger::new(chip8_with_source(Chip8::default(), PROGRAM));
+    debugger.command("step 3");
+    assert_hex_equal!("register 0", 1, debugger.chip8.registers[0x0]);
+
+    let output = debugger.command("reverse-step");
+    assert_eq!(output, "Undid 1 step(s)\n0x0204: 7001      ADD V0, 0x01\n");
+    assert_hex_equal!("register 0", 0, debugger.chip8.registers[0x0]);
+
+    debugger.command("rs 2");
+    assert_hex_equal!("program counter", 0x200, debugger.chip8.pc, 16);
+    assert_eq!(debugger.command("rs"), "No earlier state in the history\n");
+}
+
+#[test]
+fn test_debugger_reverse_step_after_continue() {
+    let mut debugger = Debugger::new(chip8_with_source(Chip8::default(), PROGRAM));
+    debugger.command("break 212");
+    debugger.command("continue");
+    debugger.command("continue");
+    assert_hex_equal!("register 0", 2, debugger.chip8.registers[0x0]);
+
+    // Undoes both continues, one frame at a time
+    let frames = debugger.history.len();
+    debugger.command(&format!("rs {}", frames));
+
+    assert_hex_equal!("program counter", 0x200, debugger.chip8.pc, 16);
+    assert_hex_equal!("register 0", 0, debugger.chip8.registers[0x0]);
+}
+
+#[test]
+fn test_debugger_reverse_step_keeps_timer_ticks_in_place() {
+    let mut debugger = Debugger::new(chip8_with_source(
+        Chip8::default(),
+        ": main v0 := 10 delay := v0 : loop jump loop",
+    ));
+    debugger.instructions_per_frame = 4;
+    debugger.command("step 3");
+    debugger.command("reverse-step");
+
+    // The frame ends after the 4th instruction, however it was reached
+    debugger.command("step");
+    assert_hex_equal!("delay timer", 10, debugger.chip8.delay_timer);
+    debugger.command("step");
+    assert_hex_equal!("delay timer", 9, debugger.chip8.delay_timer);
+}
@@ -0,0 +1,212 @@
+use hachi::{Chip8, Debugger, KeyWait, Quirks, state_slot_path};
+use std::io::ErrorKind;
+use std::path::Path;
+
+#[macro_use]
+mod common;
+
+use common::chip8_with_source;
+
+const PROGRAM: &str = "
+    : main
+        v0 := 30
+        delay := v0
+        buzzer := v0
+        i := hex v1
+    : loop
+        clear
+        sprite v1 v1 5
+        v1 += 1
+        i := hex v1
+        jump loop
+";
+
+fn save(chip8: &Chip8) -> Vec<u8> {
+    let mut state = Vec::new();
+    chip8
+        .save_state(&mut state)
+        .expect("Saving state should succeed");
+    state
+}
+
+#[test]
+fn test_round_trip_restores_machine_state() {
+    let mut chip8 = chip8_with_source(Chip8::new(Quirks::cosmac_vip()), PROGRAM);
+    chip8.run_frame(20).unwrap();
+    chip8.press_key(0x7);
+    chip8.rpl_flags[3] = 0x42;
+    let state = save(&chip8);
+
+    let mut restored = Chip8::default();
+    restored
+        .load_state(state.as_slice())
+        .expect("Loading state should succeed");
+
+    assert_eq!(restored.registers, chip8.registers);
+    assert_eq!(restored.memory, chip8.memory);
+    assert_hex_equal!("index", chip8.index, restored.index, 16);
+    assert_hex_equal!("program counter", chip8.pc, restored.pc, 16);
+    assert_hex_equal!("delay timer", 29, restored.delay_timer);
+    assert_hex_equal!("sound timer", 29, restored.sound_timer);
+    assert_eq!(restored.keypad, chip8.keypad);
+    assert_eq!(restored.rpl_flags, chip8.rpl_flags);
+    assert_eq!(restored.quirks, Quirks::cosmac_vip());
+    assert_eq!(restored.video_ascii(), chip8.video_ascii());
+}
+
+#[test]
+fn test_restored_machine_continues_identically() {
+    let mut chip8 = chip8_with_source(Chip8::default(), PROGRAM);
+    chip8.run_frame(7).unwrap();
+    let state = save(&chip8);
+
+    let mut restored = Chip8::default();
+    restored.load_state(state.as_slice()).unwrap();
+    chip8.run_frame(25).unwrap();
+    restored.run_frame(25).unwrap();
+
+    assert_eq!(restored.video_hash(), chip8.video_hash());
+    assert_hex_equal!("program counter", chip8.pc, restored.pc, 16);
+}
+
+#[test]
+fn test_round_trip_restores_xo_chip_state() {
+    let mut chip8 = Chip8::xo_chip();
+    chip8.memory[0xFFFF] = 0xAB;
+    chip8.hires = true;
+    chip8.planes = 3;
+    chip8.pitch = 100;
+    chip8.audio_pattern[0] = 0xF0;
+    chip8.key_wait = KeyWait::Pressed { vx: 2, key: 0xC };
+    let state = save(&chip8);
+
+    let mut restored = Chip8::default();
+    restored.load_state(state.as_slice()).unwrap();
+
+    assert_eq!(restored.memory.len(), 0x10000);
+    assert_hex_equal!("last memory byte", 0xAB, restored.memory[0xFFFF]);
+    assert!(restored.hires);
+    assert_hex_equal!("planes", 3, restored.planes);
+    assert_hex_equal!("pitch", 100, restored.pitch);
+    assert_eq!(restored.audio_pattern, chip8.audio_pattern);
+    assert_eq!(restored.key_wait, KeyWait::Pressed { vx: 2, key: 0xC });
+    assert_eq!(restored.quirks, Quirks::xo_chip());
+}
+
+#[test]
+fn test_state_format_header() {
+    let state = save(&Chip8::default());
+
+    assert_eq!(&state[..8], b"HACHISAV");
+    assert_eq!(&state[8..10], [1, 0], "Version should be 1");
+    let len = u32::from_le_bytes(state[10..14].try_into().unwrap()) as usize;
+    assert_eq!(state.len(), 8 + 2 + 4 + len + 4);
+}
+
+fn assert_load_fails(state: &[u8], message: &str) {
+    let mut chip8 = chip8_with_source(Chip8::default(), PROGRAM);
+    chip8.registers[0x5] = 0x55;
+
+    let error = chip8.load_state(state).expect_err("Loading should fail");
+
+    assert!(
+        error.to_string().contains(message),
+        "'{}' should contain '{}'",
+        error,
+        message
+    );
+    assert_hex_equal!("register 5", 0x55, chip8.registers[0x5]);
+    assert_hex_equal!("program counter", 0x200, chip8.pc, 16);
+}
+
+#[test]
+fn test_load_rejects_bad_magic() {
+    let mut state = save(&Chip8::default());
+    state[0] = b'X';
+
+    assert_load_fails(&state, "not a save state");
+}
+
+#[test]
+fn test_load_rejects_unsupported_version() {
+    let mut state = save(&Chip8::default());
+    state[8] = 99;
+
+    assert_load_fails(&state, "unsupported save state version 99");
+}
+
+#[test]
+fn test_load_rejects_corrupted_state() {
+    let mut state = save(&Chip8::default());
+    state[20] ^= 0xFF;
+
+    assert_load_fails(&state, "checksum mismatch");
+}
+
+#[test]
+fn test_load_rejects_truncated_state() {
+    let state = save(&Chip8::default());
+
+    let mut chip8 = Chip8::default();
+    let error = chip8
+        .load_state(&state[..state.len() - 1])
+        .expect_err("Loading should fail");
+
+    assert_eq!(error.kind(), ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn test_load_rejects_length_past_end_of_file() {
+    // A valid header claiming a 4 GiB payload, with nothing after it
+    let mut state = save(&Chip8::default())[..10].to_vec();
+    state.extend_from_slice(&u32::MAX.to_le_bytes());
+
+    let mut chip8 = Chip8::default();
+    let error = chip8
+        .load_state(&state[..])
+        .expect_err("Loading should fail");
+
+    assert_eq!(error.kind(), ErrorKind::UnexpectedEof);
+    assert!(
+        error.to_string().contains("save state truncated"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn test_state_slot_path() {
+    assert_eq!(
+        state_slot_path(Path::new("roms/pong.ch8"), 3),
+        Path::new("roms/pong.state3")
+    );
+}
+
+#[test]
+fn test_debugger_save_and_load_slots() {
+    let rom_path = std::env::temp_dir().join("hachi_save_state_test.ch8");
+    let mut debugger = Debugger::new(chip8_with_source(Chip8::default(), PROGRAM));
+    debugger.state_path = Some(rom_path.clone());
+
+    debugger.command("step 4");
+    assert_eq!(debugger.command("save 2"), "Saved slot 2\n");
+    debugger.command("step 5");
+    let output = debugger.command("load 2");
+
+    assert!(output.starts_with("Loaded slot 2\n0x0208"), "{}", output);
+    assert_hex_equal!("program counter", 0x208, debugger.chip8.pc, 16);
+    assert!(
+        debugger
+            .command("load 7")
+            .starts_with("Failed to load slot 7")
+    );
+
+    std::fs::remove_file(state_slot_path(&rom_path, 2)).unwrap();
+}
+
+#[test]
+fn test_debugger_without_state_path() {
+    let mut debugger = Debugger::new(Chip8::default());
+
+    assert_eq!(debugger.command("save"), "Save states are disabled\n");
+}
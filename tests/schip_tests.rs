@@ -0,0 +1,210 @@
+use hachi::Chip8;
+
+#[macro_use]
+mod common;
+
+use common::chip8_with_program;
+
+fn lit_pixels(chip8: &Chip8) -> Vec<(usize, usize)> {
+    let width = chip8.video_width();
+
+    (0..width * chip8.video_height())
+        .filter(|&pos| chip8.video[pos] != 0)
+        .map(|pos| (pos % width, pos / width))
+        .collect()
+}
+
+fn set_pixel(chip8: &mut Chip8, x: usize, y: usize) {
+    let width = chip8.video_width();
+    chip8.video[y * width + x] = 1;
+}
+
+#[test]
+fn test_starts_in_lores() {
+    let chip8 = Chip8::default();
+
+    assert!(!chip8.hires, "Should start in 64x32 mode");
+    assert_eq!(chip8.video_width(), 64);
+    assert_eq!(chip8.video_height(), 32);
+}
+
+#[test]
+fn test_set_hires_switches_resolution_and_clears() {
+    let mut chip8 = Chip8::default();
+    set_pixel(&mut chip8, 3, 3);
+
+    chip8.set_hires(true);
+
+    assert!(chip8.hires, "Should be in 128x64 mode");
+    assert_eq!(chip8.video_width(), 128);
+    assert_eq!(chip8.video_height(), 64);
+    assert!(lit_pixels(&chip8).is_empty(), "Display should be cleared");
+
+    chip8.set_hires(false);
+
+    assert!(!chip8.hires, "Should be back in 64x32 mode");
+}
+
+#[test]
+fn test_scroll_down() {
+    let mut chip8 = Chip8::default();
+    set_pixel(&mut chip8, 5, 0);
+    set_pixel(&mut chip8, 7, 30);
+
+    chip8.scroll_down(2);
+
+    // The pixel on row 30 scrolls off the bottom
+    assert_eq!(lit_pixels(&chip8), vec![(5, 2)]);
+}
+
+#[test]
+fn test_scroll_right() {
+    let mut chip8 = Chip8::default();
+    chip8.set_hires(true);
+    set_pixel(&mut chip8, 0, 10);
+    set_pixel(&mut chip8, 126, 11);
+
+    chip8.scroll_right();
+
+    assert_eq!(lit_pixels(&chip8), vec![(4, 10)]);
+}
+
+#[test]
+fn test_scroll_left() {
+    let mut chip8 = Chip8::default();
+    set_pixel(&mut chip8, 2, 10);
+    set_pixel(&mut chip8, 63, 11);
+
+    chip8.scroll_left();
+
+    assert_eq!(lit_pixels(&chip8), vec![(59, 11)]);
+}
+
+#[test]
+fn test_draw_in_hires_uses_full_resolution() {
+    let mut chip8 = Chip8 {
+        index: 0x300,
+        registers: {
+            let mut registers = [0; 16];
+            registers[0x0] = 100;
+            registers[0x1] = 50;
+            registers
+        },
+        ..Default::default()
+    };
+    chip8.set_hires(true);
+    chip8.memory[0x300] = 0x80;
+
+    chip8.draw_vx_vy_n(0x0, 0x1, 1).unwrap();
+
+    assert_eq!(lit_pixels(&chip8), vec![(100, 50)]);
+}
+
+#[test]
+fn test_draw_16x16_sprite() {
+    let mut chip8 = Chip8 {
+        index: 0x300,
+        registers: {
+            let mut registers = [0; 16];
+            registers[0x0] = 8;
+            registers[0x1] = 4;
+            registers
+        },
+        ..Default::default()
+    };
+    chip8.set_hires(true);
+    // First row: leftmost and rightmost pixels; last row: leftmost pixel
+    chip8.memory[0x300] = 0x80;
+    chip8.memory[0x301] = 0x01;
+    chip8.memory[0x31E] = 0x80;
+
+    chip8.draw_vx_vy_n(0x0, 0x1, 0).unwrap();
+
+    assert_eq!(lit_pixels(&chip8), vec![(8, 4), (23, 4), (8, 19)]);
+    assert_hex_equal!("register F", 0, chip8.registers[0xF]);
+
+    // Drawing again collides and erases the sprite
+    chip8.draw_vx_vy_n(0x0, 0x1, 0).unwrap();
+
+    assert!(lit_pixels(&chip8).is_empty(), "Sprite should be erased");
+    assert_hex_equal!("register F", 1, chip8.registers[0xF]);
+}
+
+#[test]
+fn test_exit_halts_interpreter() {
+    let program = [
+        0x00, 0xFD, // 0x200: EXIT
+        0x60, 0x01, // 0x202: LD V0, 0x01
+    ];
+    let mut chip8 = chip8_with_program(Chip8::default(), &program);
+
+    chip8.cycle().unwrap();
+    chip8.cycle().unwrap();
+
+    assert!(chip8.halted, "Interpreter should be halted");
+    assert_hex_equal!("program counter", 0x202, chip8.pc, 16);
+    assert_hex_equal!("register 0", 0x00, chip8.registers[0x0]);
+}
+
+#[test]
+fn test_load_big_font_vx() {
+    let mut chip8 = Chip8 {
+        registers: {
+            let mut registers = [0; 16];
+            registers[0x2] = 0x7;
+            registers
+        },
+        ..Default::default()
+    };
+
+    chip8.load_big_font_vx(0x2);
+
+    // Big font starts at 0xA0 and each character is 10 bytes long
+    assert_hex_equal!("index", 0xA0 + 0x7 * 10, chip8.index, 16);
+}
+
+#[test]
+fn test_store_and_load_flags() {
+    let mut chip8 = Chip8 {
+        registers: {
+            let mut registers = [0; 16];
+            registers[0x0] = 0x11;
+            registers[0x1] = 0x22;
+            registers[0x2] = 0x33;
+            registers
+        },
+        ..Default::default()
+    };
+
+    chip8.store_flags(0x1);
+
+    assert_eq!(chip8.rpl_flags[..3], [0x11, 0x22, 0x00]);
+
+    chip8.registers = [0; 16];
+    chip8.load_flags(0x1);
+
+    assert_eq!(chip8.registers[..3], [0x11, 0x22, 0x00]);
+}
+
+#[test]
+fn test_cycle_runs_schip_program() {
+    let program = [
+        0x00, 0xFF, // 0x200: HIGH
+        0xA0, 0xA0, // 0x202: LD I, 0x0A0 (big "0")
+        0xD0, 0x0A, // 0x204: DRW V0, V0, 10
+        0x00, 0xC1, // 0x206: SCD 1
+    ];
+    let mut chip8 = chip8_with_program(Chip8::default(), &program);
+
+    for _ in 0..4 {
+        chip8.cycle().unwrap();
+    }
+
+    // Top row of the big "0" is 0xFF, scrolled down by one
+    assert!(chip8.hires, "Should be in 128x64 mode");
+    assert!((0..8).all(|x| chip8.video[128 + x] != 0));
+    assert!(
+        (0..128).all(|x| chip8.video[x] == 0),
+        "Row 0 should be empty"
+    );
+}
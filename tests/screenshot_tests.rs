@@ -0,0 +1,198 @@
+use hachi::{Chip8, Debugger, ImageFormat, Palette, Screenshot, assemble};
+use std::io::Cursor;
+use std::path::Path;
+use std::process::Command;
+
+#[macro_use]
+mod common;
+
+use common::chip8_with_source;
+
+// Draws the font glyph "0" (F0 90 90 90 F0) at (0, 0)
+const PROGRAM: &str = ": main i := hex v0 sprite v0 v0 5 : loop jump loop";
+
+fn chip8_after_drawing(chip8: Chip8) -> Chip8 {
+    let mut chip8 = chip8_with_source(chip8, PROGRAM);
+    chip8.run_frame(3).unwrap();
+    chip8
+}
+
+fn write(screenshot: &Screenshot, chip8: &Chip8) -> Vec<u8> {
+    let mut image = Vec::new();
+    screenshot
+        .write(chip8, &mut image)
+        .expect("Writing should succeed");
+    image
+}
+
+#[test]
+fn test_pbm_screenshot() {
+    let chip8 = chip8_after_drawing(Chip8::default());
+
+    let image = write(&Screenshot::new(ImageFormat::Pbm), &chip8);
+
+    let header = b"P4\n64 32\n";
+    assert_eq!(&image[..header.len()], header);
+    let rows: Vec<&[u8]> = image[header.len()..].chunks(8).collect();
+    assert_eq!(rows.len(), 32);
+    // Lit pixels are white, which is 0 in PBM
+    assert_hex_equal!("row 0", 0x0F, rows[0][0]);
+    assert_hex_equal!("row 1", 0x6F, rows[1][0]);
+    assert_hex_equal!("row 5", 0xFF, rows[5][0]);
+}
+
+#[test]
+fn test_ppm_screenshot_uses_scale_and_palette() {
+    let chip8 = chip8_after_drawing(Chip8::default());
+    let screenshot = Screenshot {
+        scale: 2,
+        palette: Palette::parse("102030,#A0B0C0").expect("Palette should parse"),
+        ..Screenshot::new(ImageFormat::Ppm)
+    };
+
+    let image = write(&screenshot, &chip8);
+
+    let header = b"P6\n128 64\n255\n";
+    assert_eq!(&image[..header.len()], header);
+    let pixels = &image[header.len()..];
+    assert_eq!(pixels.len(), 128 * 64 * 3);
+    let pixel = |x: usize, y: usize| &pixels[(y * 128 + x) * 3..][..3];
+    assert_eq!(pixel(0, 0), [0xA0, 0xB0, 0xC0]);
+    assert_eq!(pixel(7, 1), [0xA0, 0xB0, 0xC0]);
+    assert_eq!(pixel(7, 3), [0xA0, 0xB0, 0xC0]);
+    assert_eq!(pixel(2, 3), [0x10, 0x20, 0x30]);
+    assert_eq!(pixel(8, 0), [0x10, 0x20, 0x30]);
+}
+
+#[test]
+fn test_png_screenshot_decodes() {
+    let mut chip8 = chip8_after_drawing(Chip8::default());
+    chip8.video[63] = 0b11;
+
+    let image = write(&Screenshot::new(ImageFormat::Png), &chip8);
+
+    let mut decoder = png::Decoder::new(Cursor::new(image))
+        .read_info()
+        .expect("Decoding should succeed");
+    let mut pixels = vec![0; decoder.output_buffer_size().unwrap()];
+    let info = decoder.next_frame(&mut pixels).unwrap();
+    assert_eq!((info.width, info.height), (64, 32));
+    let palette = decoder.info().palette.as_ref().unwrap();
+    assert_eq!(&palette[..6], [0, 0, 0, 0xFF, 0xFF, 0xFF]);
+    assert_eq!(&pixels[..5], [1, 1, 1, 1, 0]);
+    assert_hex_equal!("both planes", 3, pixels[63]);
+}
+
+#[test]
+fn test_screenshot_of_hires_display() {
+    let mut chip8 = Chip8::new(hachi::Quirks::super_chip());
+    chip8.hires = true;
+
+    let image = write(&Screenshot::new(ImageFormat::Pbm), &chip8);
+
+    assert!(image.starts_with(b"P4\n128 64\n"));
+}
+
+#[test]
+fn test_screenshot_rejects_zero_scale() {
+    let screenshot = Screenshot {
+        scale: 0,
+        ..Screenshot::new(ImageFormat::Ppm)
+    };
+
+    assert!(
+        screenshot
+            .write(&Chip8::default(), &mut Vec::new())
+            .is_err()
+    );
+}
+
+#[test]
+fn test_image_format_from_path() {
+    assert_eq!(
+        ImageFormat::from_path(Path::new("shot.PNG")),
+        Some(ImageFormat::Png)
+    );
+    assert_eq!(
+        ImageFormat::from_path(Path::new("a/shot.pbm")),
+        Some(ImageFormat::Pbm)
+    );
+    assert_eq!(ImageFormat::from_path(Path::new("shot.bmp")), None);
+    assert_eq!(ImageFormat::from_path(Path::new("shot")), None);
+}
+
+#[test]
+fn test_palette_parse() {
+    let palette = Palette::parse("ff0000").unwrap();
+
+    assert_eq!(palette.0[0], [0xFF, 0, 0]);
+    assert_eq!(palette.0[1], Palette::default().0[1]);
+    assert_eq!(Palette::parse("ff00"), None);
+    assert_eq!(Palette::parse("gg0000"), None);
+    assert_eq!(Palette::parse(&["000000"; 5].join(",")), None);
+}
+
+#[test]
+fn test_debugger_screenshot() {
+    let path = std::env::temp_dir().join("hachi_debugger_screenshot.pbm");
+    let mut debugger = Debugger::new(chip8_after_drawing(Chip8::default()));
+
+    let output = debugger.command(&format!("screenshot {} 2", path.display()));
+
+    assert_eq!(output, format!("Saved {}\n", path.display()));
+    assert!(std::fs::read(&path).unwrap().starts_with(b"P4\n128 64\n"));
+    assert!(
+        debugger
+            .command("screenshot shot.bmp")
+            .starts_with("Unknown image format")
+    );
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_cli_screenshot_at_frame() {
+    let dir = std::env::temp_dir();
+    let rom_path = dir.join("hachi_screenshot_cli_test.ch8");
+    let image_path = dir.join("hachi_screenshot_cli_test.ppm");
+    // Draws the digit of the frame count, after a delay
+    let rom = assemble(
+        ": main v1 := 30 delay := v1
+         : wait v1 := delay if v1 != 0 then jump wait
+         i := hex v0 sprite v0 v0 5 : loop jump loop",
+    )
+    .unwrap();
+    std::fs::write(&rom_path, rom).unwrap();
+
+    let screenshot_at = |frame: &str| {
+        let output = Command::new(env!("CARGO_BIN_EXE_hachi"))
+            .args(["run", "--headless", "--scale", "3", "--screenshot"])
+            .arg(&image_path)
+            .args(["--screenshot-at-frame", frame])
+            .arg(&rom_path)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "Running should succeed");
+        std::fs::read(&image_path).unwrap()
+    };
+    let before = screenshot_at("10");
+    let after = screenshot_at("40");
+
+    let header = b"P6\n192 96\n255\n";
+    assert!(before.starts_with(header));
+    assert!(before[header.len()..].iter().all(|&byte| byte == 0));
+    assert_eq!(&after[header.len()..][..3], [0xFF, 0xFF, 0xFF]);
+
+    std::fs::remove_file(image_path).unwrap();
+}
+
+#[test]
+fn test_cli_screenshot_requires_headless() {
+    let output = Command::new(env!("CARGO_BIN_EXE_hachi"))
+        .args(["run", "--screenshot", "shot.png", "rom.ch8"])
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--screenshot requires --headless"));
+}
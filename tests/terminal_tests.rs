@@ -0,0 +1,80 @@
+use hachi::Chip8;
+use hachi::terminal::{Render, key_for_char};
+
+#[macro_use]
+mod common;
+
+use common::chip8_with_program;
+
+// Draws the font glyph "0" at (0, 0) and loops forever
+const PROGRAM: [u8; 6] = [
+    0xA0, 0x50, // 0x200: LD I, 0x050
+    0xD0, 0x05, // 0x202: DRW V0, V0, 5
+    0x12, 0x04, // 0x204: JP 0x204
+];
+
+fn chip8_after_drawing() -> Chip8 {
+    let mut chip8 = chip8_with_program(Chip8::default(), &PROGRAM);
+    chip8.run_frame(3).unwrap();
+    chip8
+}
+
+#[test]
+fn test_keyboard_layout() {
+    let layout = ["1234", "qwer", "asdf", "zxcv"];
+    let keypad = [
+        [0x1, 0x2, 0x3, 0xC],
+        [0x4, 0x5, 0x6, 0xD],
+        [0x7, 0x8, 0x9, 0xE],
+        [0xA, 0x0, 0xB, 0xF],
+    ];
+
+    for (row, keys) in layout.iter().zip(keypad) {
+        for (c, key) in row.chars().zip(keys) {
+            assert_eq!(key_for_char(c), Some(key), "Key for '{}'", c);
+        }
+    }
+}
+
+#[test]
+fn test_keyboard_layout_ignores_case_and_other_keys() {
+    assert_eq!(key_for_char('Q'), Some(0x4));
+    assert_eq!(key_for_char('5'), None);
+    assert_eq!(key_for_char('p'), None);
+}
+
+#[test]
+fn test_render_half_blocks() {
+    let render = Render::HalfBlock.render(&chip8_after_drawing());
+    let lines: Vec<&str> = render.lines().collect();
+
+    assert_eq!(lines.len(), 16);
+    assert!(lines.iter().all(|line| line.chars().count() == 64));
+    // Rows 0-1: ####, #..#  Rows 2-3: #..#, #..#  Row 4: ####
+    assert!(lines[0].starts_with("█▀▀█ "));
+    assert!(lines[1].starts_with("█  █ "));
+    assert!(lines[2].starts_with("▀▀▀▀ "));
+    assert!(lines[3..].iter().all(|line| line.trim().is_empty()));
+}
+
+#[test]
+fn test_render_braille() {
+    let render = Render::Braille.render(&chip8_after_drawing());
+    let lines: Vec<&str> = render.lines().collect();
+
+    assert_eq!(lines.len(), 8);
+    assert!(lines.iter().all(|line| line.chars().count() == 32));
+    // Rows 0-3 of columns 0-1: ##, #., #., #.  and of columns 2-3: ##, .#, .#, .#
+    assert!(lines[0].starts_with("\u{284F}\u{28B9}\u{2800}"));
+    // Row 4: ##, ##
+    assert!(lines[1].starts_with("\u{2809}\u{2809}\u{2800}"));
+}
+
+#[test]
+fn test_render_hires() {
+    let mut chip8 = Chip8::default();
+    chip8.set_hires(true);
+
+    assert_eq!(Render::HalfBlock.render(&chip8).lines().count(), 32);
+    assert_eq!(Render::Braille.render(&chip8).lines().count(), 16);
+}
@@ -0,0 +1,223 @@
+use hachi::Chip8;
+use std::io::Cursor;
+
+#[macro_use]
+mod common;
+
+use common::chip8_with_program;
+
+#[test]
+fn test_xo_chip_has_64k_memory() {
+    let chip8 = Chip8::xo_chip();
+
+    assert_eq!(chip8.memory.len(), 0x10000);
+    assert_eq!(Chip8::default().memory.len(), 0x1000);
+}
+
+#[test]
+fn test_load_rom_larger_than_4k() {
+    let rom = vec![0xAB; 0x2000];
+    let mut chip8 = Chip8::xo_chip();
+
+    let result = chip8.load_rom_from_reader(Cursor::new(rom));
+
+    assert!(result.is_ok(), "Loading 8 KiB ROM should succeed");
+    assert_hex_equal!("last ROM byte", 0xAB, chip8.memory[0x21FF]);
+}
+
+#[test]
+fn test_load_long_index() {
+    let mut chip8 = chip8_with_program(
+        Chip8::xo_chip(),
+        &[
+            0xF0, 0x00, 0xBE, 0xEF, // F000 BEEF
+            0x60, 0x01, // LD V0, 0x01
+        ],
+    );
+
+    chip8.cycle().unwrap();
+
+    assert_hex_equal!("index", 0xBEEF, chip8.index, 16);
+    assert_hex_equal!("program counter", 0x204, chip8.pc, 16);
+}
+
+#[test]
+fn test_skip_jumps_over_long_instruction() {
+    let mut chip8 = chip8_with_program(
+        Chip8::xo_chip(),
+        &[
+            0x30, 0x00, // SE V0, 0x00
+            0xF0, 0x00, 0xBE, 0xEF, // F000 BEEF (skipped)
+            0x61, 0x01, // LD V1, 0x01
+        ],
+    );
+
+    chip8.cycle().unwrap();
+
+    assert_hex_equal!("program counter", 0x206, chip8.pc, 16);
+
+    chip8.cycle().unwrap();
+
+    assert_hex_equal!("index", 0x0000, chip8.index, 16);
+    assert_hex_equal!("register 1", 0x01, chip8.registers[0x1]);
+}
+
+#[test]
+fn test_store_range_ascending() {
+    let mut chip8 = Chip8 {
+        index: 0x300,
+        registers: {
+            let mut registers = [0; 16];
+            registers[0x2] = 0x22;
+            registers[0x3] = 0x33;
+            registers[0x4] = 0x44;
+            registers
+        },
+        ..Chip8::xo_chip()
+    };
+
+    chip8.store_range(0x2, 0x4).unwrap();
+
+    assert_eq!(chip8.memory[0x300..0x304], [0x22, 0x33, 0x44, 0x00]);
+    assert_hex_equal!("index", 0x300, chip8.index, 16);
+}
+
+#[test]
+fn test_store_range_descending() {
+    let mut chip8 = Chip8 {
+        index: 0x300,
+        registers: {
+            let mut registers = [0; 16];
+            registers[0x2] = 0x22;
+            registers[0x3] = 0x33;
+            registers[0x4] = 0x44;
+            registers
+        },
+        ..Chip8::xo_chip()
+    };
+
+    chip8.store_range(0x4, 0x2).unwrap();
+
+    assert_eq!(chip8.memory[0x300..0x303], [0x44, 0x33, 0x22]);
+}
+
+#[test]
+fn test_load_range() {
+    let mut chip8 = Chip8 {
+        index: 0x300,
+        ..Chip8::xo_chip()
+    };
+    chip8.memory[0x300..0x303].copy_from_slice(&[0xAA, 0xBB, 0xCC]);
+
+    chip8.load_range(0x5, 0x7).unwrap();
+
+    assert_eq!(chip8.registers[0x4..0x9], [0x00, 0xAA, 0xBB, 0xCC, 0x00]);
+    assert_hex_equal!("index", 0x300, chip8.index, 16);
+}
+
+#[test]
+fn test_select_planes() {
+    let mut chip8 = Chip8::xo_chip();
+
+    assert_hex_equal!("default planes", 0b01, chip8.planes);
+
+    chip8.select_planes(0x3);
+
+    assert_hex_equal!("planes", 0b11, chip8.planes);
+}
+
+#[test]
+fn test_draw_on_both_planes() {
+    let mut chip8 = Chip8 {
+        index: 0x300,
+        ..Chip8::xo_chip()
+    };
+    chip8.memory[0x300] = 0xC0; // plane 1: pixels 0 and 1
+    chip8.memory[0x301] = 0x60; // plane 2: pixels 1 and 2
+    chip8.select_planes(0x3);
+
+    chip8.draw_vx_vy_n(0x0, 0x0, 1).unwrap();
+
+    assert_eq!(chip8.video[0..4], [0b01, 0b11, 0b10, 0b00]);
+    assert_hex_equal!("register F", 0, chip8.registers[0xF]);
+}
+
+#[test]
+fn test_draw_on_second_plane_only() {
+    let mut chip8 = Chip8 {
+        index: 0x300,
+        ..Chip8::xo_chip()
+    };
+    chip8.memory[0x300] = 0x80;
+    chip8.video[0] = 0b01;
+    chip8.select_planes(0x2);
+
+    chip8.draw_vx_vy_n(0x0, 0x0, 1).unwrap();
+
+    // No collision because plane 1 isn't drawn on
+    assert_hex_equal!("pixel 0", 0b11, chip8.video[0]);
+    assert_hex_equal!("register F", 0, chip8.registers[0xF]);
+
+    chip8.draw_vx_vy_n(0x0, 0x0, 1).unwrap();
+
+    assert_hex_equal!("pixel 0", 0b01, chip8.video[0]);
+    assert_hex_equal!("register F", 1, chip8.registers[0xF]);
+}
+
+#[test]
+fn test_clear_display_only_clears_selected_planes() {
+    let mut chip8 = Chip8 {
+        video: [0b11; 128 * 64],
+        ..Chip8::xo_chip()
+    };
+    chip8.select_planes(0x2);
+
+    chip8.clear_display();
+
+    assert!(chip8.video.iter().all(|&pixel| pixel == 0b01));
+}
+
+#[test]
+fn test_scroll_up_only_moves_selected_planes() {
+    let mut chip8 = Chip8::xo_chip();
+    chip8.video[64 * 3 + 5] = 0b11;
+    chip8.select_planes(0x1);
+
+    chip8.scroll_up(2);
+
+    assert_hex_equal!("pixel (5, 1)", 0b01, chip8.video[64 + 5]);
+    assert_hex_equal!("pixel (5, 3)", 0b10, chip8.video[64 * 3 + 5]);
+}
+
+#[test]
+fn test_load_audio_pattern() {
+    let mut chip8 = Chip8 {
+        index: 0x300,
+        ..Chip8::xo_chip()
+    };
+    for (offset, byte) in chip8.memory[0x300..0x310].iter_mut().enumerate() {
+        *byte = offset as u8;
+    }
+
+    chip8.load_audio_pattern().unwrap();
+
+    assert_eq!(chip8.audio_pattern, std::array::from_fn(|i| i as u8));
+}
+
+#[test]
+fn test_load_pitch_vx() {
+    let mut chip8 = Chip8 {
+        registers: {
+            let mut registers = [0; 16];
+            registers[0x6] = 0x70;
+            registers
+        },
+        ..Chip8::xo_chip()
+    };
+
+    assert_hex_equal!("default pitch", 64, chip8.pitch);
+
+    chip8.load_pitch_vx(0x6);
+
+    assert_hex_equal!("pitch", 0x70, chip8.pitch);
+}